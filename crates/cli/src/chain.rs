@@ -0,0 +1,355 @@
+//! Program-derived addresses and transaction submission for Chomping Glass games.
+
+use anyhow::{anyhow, Context, Result};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    account::Account, pubkey::Pubkey, signature::Signature, transaction::Transaction,
+};
+use std::str::FromStr;
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The game account the program keeps for `player`.
+pub fn game_pda(player: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[player.as_ref()], program_id).0
+}
+
+/// Pair each player with their game PDA, in input order.
+pub fn derive_game_pdas(players: &[Pubkey], program_id: &Pubkey) -> Vec<(Pubkey, Pubkey)> {
+    players
+        .iter()
+        .map(|player| (*player, game_pda(player, program_id)))
+        .collect()
+}
+
+/// Format account bytes as hex, 16 per line, each line prefixed with its offset.
+pub fn hex_dump(data: &[u8]) -> String {
+    data.chunks(16)
+        .enumerate()
+        .map(|(idx, chunk)| {
+            let bytes: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+            format!("{:04x}: {}", idx * 16, bytes.join(" "))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Read access to account data, so the fetch path can be exercised without RPC.
+pub trait AccountReader {
+    /// The account's data, or `None` if it does not exist.
+    fn read_account(&self, address: &Pubkey) -> Result<Option<Vec<u8>>>;
+}
+
+impl AccountReader for RpcClient {
+    fn read_account(&self, address: &Pubkey) -> Result<Option<Vec<u8>>> {
+        match self.get_account_data(address) {
+            Ok(data) => Ok(Some(data)),
+            Err(err) if crate::account_missing(&err) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// Timings from [`ping`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PingReport {
+    pub game_pda: Pubkey,
+    /// Time spent deriving the PDA.
+    pub derive: Duration,
+    /// Round-trip time of the account read.
+    pub fetch: Duration,
+    /// Account size in bytes, or `None` if no game account exists yet.
+    pub account_len: Option<usize>,
+}
+
+impl std::fmt::Display for PingReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Game PDA: {}", self.game_pda)?;
+        writeln!(f, "Derive:   {:.3} ms", self.derive.as_secs_f64() * 1000.0)?;
+        writeln!(f, "Fetch:    {:.3} ms", self.fetch.as_secs_f64() * 1000.0)?;
+        match self.account_len {
+            Some(len) => writeln!(f, "Account:  exists ({} bytes)", len),
+            None => writeln!(f, "Account:  does not exist"),
+        }
+    }
+}
+
+/// Time deriving `player`'s game PDA and reading it, without solving anything.
+pub fn ping(
+    reader: &dyn AccountReader,
+    player: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<PingReport> {
+    let started = Instant::now();
+    let game_pda = game_pda(player, program_id);
+    let derive = started.elapsed();
+    let started = Instant::now();
+    let data = reader.read_account(&game_pda)?;
+    let fetch = started.elapsed();
+    Ok(PingReport {
+        game_pda,
+        derive,
+        fetch,
+        account_len: data.map(|data| data.len()),
+    })
+}
+
+/// What a program ID points at on the cluster, from [`classify_program`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProgramStatus {
+    /// A deployed program.
+    Executable,
+    /// The account exists but is not a program, e.g. a wallet or data account.
+    NotExecutable,
+    /// No account at that address on this cluster.
+    Missing,
+}
+
+/// Classify the account fetched for a program ID.
+pub fn classify_program(account: Option<&Account>) -> ProgramStatus {
+    match account {
+        Some(account) if account.executable => ProgramStatus::Executable,
+        Some(_) => ProgramStatus::NotExecutable,
+        None => ProgramStatus::Missing,
+    }
+}
+
+/// Fetch `program_id` and report whether it is a deployed program.
+pub fn check_program(rpc: &RpcClient, program_id: &Pubkey) -> Result<ProgramStatus> {
+    match rpc.get_account(program_id) {
+        Ok(account) => Ok(classify_program(Some(&account))),
+        Err(err) if crate::account_missing(&err) => Ok(classify_program(None)),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// An endpoint that can send a signed transaction and wait for confirmation.
+pub trait Submitter: Send + Sync {
+    /// Label used in error messages, e.g. the RPC URL.
+    fn name(&self) -> String;
+    fn submit(&self, tx: &Transaction) -> Result<Signature>;
+}
+
+impl Submitter for RpcClient {
+    fn name(&self) -> String {
+        self.url()
+    }
+
+    fn submit(&self, tx: &Transaction) -> Result<Signature> {
+        Ok(self.send_and_confirm_transaction(tx)?)
+    }
+}
+
+/// Send `tx` through every endpoint at once and return the first confirmed
+/// signature. Every endpoint carries the same signed transaction, so they can
+/// only ever confirm one signature; slower endpoints are left to finish in the
+/// background. Fails only if every endpoint fails.
+pub fn race_submit(submitters: &[Arc<dyn Submitter>], tx: &Transaction) -> Result<Signature> {
+    if submitters.is_empty() {
+        return Err(anyhow!("no RPC endpoints to submit to"));
+    }
+    let (sender, receiver) = mpsc::channel();
+    for submitter in submitters {
+        let submitter = Arc::clone(submitter);
+        let sender = sender.clone();
+        let tx = tx.clone();
+        thread::spawn(move || {
+            let result = submitter.submit(&tx);
+            // The receiver is gone once another endpoint has confirmed.
+            let _ = sender.send((submitter.name(), result));
+        });
+    }
+    drop(sender);
+
+    let mut failures = Vec::new();
+    for (name, result) in receiver {
+        match result {
+            Ok(signature) => return Ok(signature),
+            Err(err) => failures.push(format!("{}: {:#}", name, err)),
+        }
+    }
+    Err(anyhow!(
+        "every RPC endpoint failed:\n  {}",
+        failures.join("\n  ")
+    ))
+}
+
+/// Parse one base58 public key per line, skipping blank lines and `#` comments.
+pub fn parse_players(text: &str) -> Result<Vec<Pubkey>> {
+    text.lines()
+        .enumerate()
+        .map(|(idx, line)| (idx, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(idx, line)| {
+            Pubkey::from_str(line)
+                .with_context(|| format!("line {}: invalid public key {:?}", idx + 1, line))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(raw: &str) -> Pubkey {
+        Pubkey::from_str(raw).unwrap()
+    }
+
+    #[test]
+    fn test_classify_program_accounts() {
+        let program = Account {
+            executable: true,
+            ..Account::default()
+        };
+        assert_eq!(classify_program(Some(&program)), ProgramStatus::Executable);
+        let wallet = Account {
+            lamports: 1_000_000,
+            ..Account::default()
+        };
+        assert_eq!(
+            classify_program(Some(&wallet)),
+            ProgramStatus::NotExecutable
+        );
+        assert_eq!(classify_program(None), ProgramStatus::Missing);
+    }
+
+    #[test]
+    fn test_derive_game_pdas_known_players() {
+        let program = key(crate::DEFAULT_PROGRAM);
+        let players = [
+            key("11111111111111111111111111111111"),
+            key("EGJnqcxVbhJFJ6Xnchtaw8jmPSvoLXfN2gWsY9Etz5SZ"),
+        ];
+        let pdas = derive_game_pdas(&players, &program);
+        assert_eq!(
+            pdas,
+            vec![
+                (
+                    players[0],
+                    key("BDkXzEE87xDN24be4Giig3P3vKFYa8iWWQwUoGDqtAWt")
+                ),
+                (
+                    players[1],
+                    key("2uMsNXUSVpCbR32JNCsL2czdxCydHe4VgXfd6CiCazSr")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hex_dump() {
+        assert_eq!(hex_dump(&[]), "");
+        assert_eq!(hex_dump(&[0xc0, 0x80, 0x00, 0xff]), "0000: c0 80 00 ff");
+        let data: Vec<u8> = (0..18).collect();
+        assert_eq!(
+            hex_dump(&data),
+            "0000: 00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f\n0010: 10 11"
+        );
+    }
+
+    struct MockSubmitter {
+        name: &'static str,
+        delay_ms: u64,
+        result: std::result::Result<Signature, &'static str>,
+    }
+
+    impl Submitter for MockSubmitter {
+        fn name(&self) -> String {
+            self.name.to_string()
+        }
+
+        fn submit(&self, _tx: &Transaction) -> Result<Signature> {
+            thread::sleep(std::time::Duration::from_millis(self.delay_ms));
+            self.result.map_err(anyhow::Error::msg)
+        }
+    }
+
+    fn mock(
+        name: &'static str,
+        delay_ms: u64,
+        result: std::result::Result<Signature, &'static str>,
+    ) -> Arc<dyn Submitter> {
+        Arc::new(MockSubmitter {
+            name,
+            delay_ms,
+            result,
+        })
+    }
+
+    #[test]
+    fn test_race_submit_takes_first_success() {
+        let signature = Signature::from([7; 64]);
+        let submitters = [
+            mock("down", 0, Err("connection refused")),
+            mock("slow", 2_000, Ok(signature)),
+            mock("fast", 10, Ok(signature)),
+        ];
+        let started = std::time::Instant::now();
+        let result = race_submit(&submitters, &Transaction::default()).unwrap();
+        assert_eq!(result, signature);
+        assert!(started.elapsed() < std::time::Duration::from_millis(1_000));
+    }
+
+    #[test]
+    fn test_race_submit_reports_every_failure() {
+        let submitters = [
+            mock("a", 0, Err("timed out")),
+            mock("b", 5, Err("blockhash not found")),
+        ];
+        let err = race_submit(&submitters, &Transaction::default())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("a: timed out"));
+        assert!(err.contains("b: blockhash not found"));
+        assert!(race_submit(&[], &Transaction::default()).is_err());
+    }
+
+    struct MockReader {
+        delay_ms: u64,
+        data: Option<Vec<u8>>,
+    }
+
+    impl AccountReader for MockReader {
+        fn read_account(&self, _address: &Pubkey) -> Result<Option<Vec<u8>>> {
+            thread::sleep(Duration::from_millis(self.delay_ms));
+            Ok(self.data.clone())
+        }
+    }
+
+    #[test]
+    fn test_ping_reports_fetch_latency_and_existence() {
+        let player = key("11111111111111111111111111111111");
+        let program = key(crate::DEFAULT_PROGRAM);
+        let reader = MockReader {
+            delay_ms: 20,
+            data: Some(vec![0; 5]),
+        };
+        let report = ping(&reader, &player, &program).unwrap();
+        assert_eq!(report.game_pda, game_pda(&player, &program));
+        assert!(report.fetch >= Duration::from_millis(20));
+        assert_eq!(report.account_len, Some(5));
+        let text = report.to_string();
+        assert!(text.contains("Fetch:"));
+        assert!(text.contains("exists (5 bytes)"));
+
+        let missing = MockReader {
+            delay_ms: 0,
+            data: None,
+        };
+        let report = ping(&missing, &player, &program).unwrap();
+        assert_eq!(report.account_len, None);
+        assert!(report.to_string().contains("does not exist"));
+    }
+
+    #[test]
+    fn test_parse_players() {
+        let text = "# leaderboard\n11111111111111111111111111111111\n\n  EGJnqcxVbhJFJ6Xnchtaw8jmPSvoLXfN2gWsY9Etz5SZ  \n";
+        let players = parse_players(text).unwrap();
+        assert_eq!(players.len(), 2);
+        assert_eq!(players[1], key(crate::FEE_COLLECTOR));
+
+        let err = parse_players("11111111111111111111111111111111\nnot-a-key\n").unwrap_err();
+        assert!(err.to_string().starts_with("line 2"));
+    }
+}
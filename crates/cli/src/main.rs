@@ -1,5 +1,6 @@
 use anyhow::{anyhow, Context, Result};
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
+use indicatif::ProgressBar;
 use serde::Serialize;
 use solana_client::{
     client_error::{ClientError, ClientErrorKind},
@@ -10,12 +11,34 @@ use solana_sdk::{
     commitment_config::CommitmentConfig,
     instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
-    signature::{read_keypair_file, Signer},
+    signature::{read_keypair_file, Keypair, Signature, Signer},
     transaction::Transaction,
 };
-use solver_core::{export_policy_json, BoardState, Move, Solver};
-use std::path::PathBuf;
+use solver_core::book::OpeningBook;
+use solver_core::notation::{line_to_algebraic, parse_algebraic, to_algebraic};
+use solver_core::strategy::{self_play, OptimalStrategy, RandomStrategy, Strategy};
+use solver_core::{
+    enumerate_states, export_policy_json_resumable, export_response_map, opcode,
+    p_position_cheatsheet, solution_summary, Blunder, BoardState, DisplayMove, Move, Player,
+    PolicyKeyFormat, PolicyMoveFormat, Solver, Tiebreak, Verdict,
+};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+mod chain;
+mod onchain;
+mod source;
+mod vectors;
+#[cfg(feature = "vision")]
+mod vision;
+
+use chain::AccountReader;
+use onchain::decode_board_at;
+use source::{AccountDump, ChainSource, FileSource, ManualSource, StateSource};
 
 const DEFAULT_RPC: &str = "https://api.mainnet-beta.solana.com";
 const DEFAULT_PROGRAM: &str = "ChompZg47TcVy5fk2LxPEpW6SytFYBES5SHoqgrm8A4D";
@@ -33,9 +56,51 @@ enum Commands {
     /// Suggest winning moves for a board state.
     Suggest(SuggestArgs),
     /// Export the policy table to JSON.
-    ExportPolicy { output: PathBuf },
+    ExportPolicy {
+        output: PathBuf,
+        /// Continue an interrupted export instead of starting over.
+        #[arg(long)]
+        resume: bool,
+        /// Key states by their heights array or by the packed u64.
+        #[arg(long, default_value_t = PolicyKeyFormat::Heights)]
+        key_format: PolicyKeyFormat,
+        /// Write moves as zero-indexed objects (structured) or algebraic strings.
+        #[arg(long, default_value_t = PolicyMoveFormat::Structured)]
+        move_format: PolicyMoveFormat,
+    },
+    /// Export one recommended opcode per state as a compact binary table.
+    ExportResponses {
+        output: PathBuf,
+        /// Which winning move to store when there are several.
+        #[arg(long, default_value_t = Tiebreak::First)]
+        tiebreak: Tiebreak,
+    },
     /// Play a move on-chain with your wallet.
     Play(PlayArgs),
+    /// Keep playing optimal moves on-chain until the game ends.
+    Autoplay(AutoplayArgs),
+    /// Print the opening book of perfect-play recommendations.
+    Book(BookArgs),
+    /// Print the game PDA for each player in a file.
+    Pdas(PdasArgs),
+    /// Time the game account lookup without solving anything.
+    Ping(PingArgs),
+    /// Check that the program ID is a deployed program on the cluster.
+    CheckProgram(CheckProgramArgs),
+    /// Play against the solver in the terminal.
+    PlayLocal(PlayLocalArgs),
+    /// Replay a move history and print the next move for the side to play.
+    Next(NextArgs),
+    /// Write reference state transitions for testing the on-chain program.
+    GenVectors(GenVectorsArgs),
+    /// Play seeded random games against each other and tally the winners.
+    Simulate(SimulateArgs),
+    /// Print every losing position, grouped by candies left.
+    Cheatsheet,
+    /// Summarize the solved game.
+    About,
+    /// Print the winning positions with the most winning moves.
+    Widest,
 }
 
 #[derive(Parser, Debug)]
@@ -43,6 +108,9 @@ struct SuggestArgs {
     /// Manual column heights, e.g. "0,0,-1,-1,-1,-1,-1,-1".
     #[arg(long)]
     state: Option<String>,
+    /// Read the board from a file saved in the compact format (e.g. "11000000").
+    #[arg(long)]
+    state_file: Option<PathBuf>,
     /// RPC endpoint when fetching live state.
     #[arg(long, default_value = DEFAULT_RPC)]
     rpc_url: String,
@@ -55,6 +123,43 @@ struct SuggestArgs {
     /// Emit JSON instead of text.
     #[arg(long)]
     json: bool,
+    /// Analyze any well-formed staircase, even one standard play cannot reach.
+    #[arg(long)]
+    allow_unreachable: bool,
+    /// How to pick the recommended move among winners: first, fastest or most-eaten.
+    #[arg(long, default_value_t = Tiebreak::First)]
+    tiebreak: Tiebreak,
+    /// Print the raw game account bytes (hex) to stderr before decoding. Chain fetches only.
+    #[arg(long)]
+    dump_account: bool,
+    /// Header bytes to skip in the game account before the row bytes.
+    #[arg(long, default_value_t = 0)]
+    data_offset: usize,
+    /// Starting board assumed when the player has no game account yet.
+    #[arg(long)]
+    handicap: Option<String>,
+    /// Report how long resolving the board and solving it took.
+    #[arg(long)]
+    timings: bool,
+    /// Column order of the `--state` heights.
+    #[arg(long, value_enum, default_value_t = HeightOrder::Ltr)]
+    order: HeightOrder,
+    /// Column order of the heights in the JSON `board` field.
+    #[arg(long, value_enum, default_value_t = HeightOrder::Ltr)]
+    output_order: HeightOrder,
+    /// Print only the instruction opcode of the suggested move, e.g. "0x12".
+    #[arg(long)]
+    opcode: bool,
+    /// Print a single line, e.g. "WIN b1 | rec b1", for shell scripts.
+    #[arg(long)]
+    oneline: bool,
+    /// Keep only the best K moves in the ranked move list (default: all).
+    #[arg(long)]
+    top: Option<usize>,
+    /// Read the board from an image of the grid instead.
+    #[cfg(feature = "vision")]
+    #[arg(long)]
+    image: Option<PathBuf>,
 }
 
 #[derive(Parser, Debug)]
@@ -62,15 +167,19 @@ struct PlayArgs {
     /// Signing keypair JSON path.
     #[arg(long)]
     wallet: PathBuf,
-    /// RPC endpoint.
+    /// RPC endpoint; repeat to send through several and take the first confirmation.
+    /// The first one is also used to read state and fetch the blockhash.
     #[arg(long, default_value = DEFAULT_RPC)]
-    rpc_url: String,
+    rpc_url: Vec<String>,
     /// Program ID to target.
     #[arg(long, default_value = DEFAULT_PROGRAM)]
     program: String,
     /// Manual board state override.
     #[arg(long)]
     state: Option<String>,
+    /// Board state override loaded from a compact-format file.
+    #[arg(long)]
+    state_file: Option<PathBuf>,
     /// Explicit row (1-indexed).
     #[arg(long)]
     row: Option<u8>,
@@ -80,73 +189,657 @@ struct PlayArgs {
     /// Print the transaction without sending.
     #[arg(long)]
     dry_run: bool,
+    /// Also print the opponent's best reply to the chosen move.
+    #[arg(long)]
+    show_reply: bool,
+    /// Account ordering expected by the target program version.
+    #[arg(long, value_enum, default_value_t = AccountLayout::V1)]
+    account_layout: AccountLayout,
+    /// Pass the fee collector read-only, for program versions that reject it as writable.
+    #[arg(long)]
+    fee_collector_readonly: bool,
+    /// Send without asking for confirmation, even on an interactive terminal.
+    #[arg(long)]
+    yes: bool,
+    /// Header bytes to skip in the game account before the row bytes.
+    #[arg(long, default_value_t = 0)]
+    data_offset: usize,
+}
+
+#[derive(Parser, Debug)]
+struct PlayLocalArgs {
+    /// Start from this board (heights, e.g. "0,0,-1,-1,-1,-1,-1,-1") instead of a full one.
+    #[arg(long)]
+    handicap: Option<String>,
+}
+
+#[derive(Parser, Debug)]
+struct NextArgs {
+    /// Moves played so far in algebraic notation, e.g. "b1 a2"; empty for a new game.
+    #[arg(long, default_value = "")]
+    history: String,
+    /// How to pick the recommended move among winners: first, fastest or most-eaten.
+    #[arg(long, default_value_t = Tiebreak::First)]
+    tiebreak: Tiebreak,
+}
+
+#[derive(Parser, Debug)]
+struct GenVectorsArgs {
+    /// Where to write the JSON array of vectors.
+    #[arg(long)]
+    out: PathBuf,
+    /// Plies of the opening book whose positions are covered.
+    #[arg(long, default_value_t = 2)]
+    depth: usize,
+}
+
+#[derive(Parser, Debug)]
+struct SimulateArgs {
+    /// Number of games to play.
+    #[arg(long, default_value_t = 100)]
+    games: usize,
+    /// Base seed; game `i` uses `seed + i`.
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    /// Print one JSON line per game as it finishes instead of a summary.
+    #[arg(long)]
+    jsonl: bool,
+}
+
+#[derive(Parser, Debug)]
+struct AutoplayArgs {
+    /// Signing keypair JSON path.
+    #[arg(long)]
+    wallet: PathBuf,
+    /// RPC endpoint.
+    #[arg(long, default_value = DEFAULT_RPC)]
+    rpc_url: String,
+    /// Program ID to target.
+    #[arg(long, default_value = DEFAULT_PROGRAM)]
+    program: String,
+    /// Account ordering expected by the target program version.
+    #[arg(long, value_enum, default_value_t = AccountLayout::V1)]
+    account_layout: AccountLayout,
+    /// Pass the fee collector read-only, for program versions that reject it as writable.
+    #[arg(long)]
+    fee_collector_readonly: bool,
+    /// Seconds to wait between polls while the opponent is thinking.
+    #[arg(long, default_value_t = 2)]
+    poll_secs: u64,
+    /// Header bytes to skip in the game account before the row bytes.
+    #[arg(long, default_value_t = 0)]
+    data_offset: usize,
+}
+
+#[derive(Parser, Debug)]
+struct BookArgs {
+    /// Number of plies from the empty board to include.
+    #[arg(long, default_value_t = 2)]
+    depth: usize,
+    /// Output format.
+    #[arg(long, value_enum, default_value_t = BookFormat::Md)]
+    format: BookFormat,
+}
+
+#[derive(Parser, Debug)]
+struct PdasArgs {
+    /// File with one player public key per line.
+    #[arg(long)]
+    players_file: PathBuf,
+    /// Program ID the PDAs belong to.
+    #[arg(long, default_value = DEFAULT_PROGRAM)]
+    program: String,
+}
+
+#[derive(Parser, Debug)]
+struct PingArgs {
+    /// RPC endpoint to time.
+    #[arg(long, default_value = DEFAULT_RPC)]
+    rpc_url: String,
+    /// Player public key for PDA derivation.
+    #[arg(long)]
+    player: String,
+    /// Program ID the PDA belongs to.
+    #[arg(long, default_value = DEFAULT_PROGRAM)]
+    program: String,
+}
+
+#[derive(Parser, Debug)]
+struct CheckProgramArgs {
+    /// RPC endpoint of the cluster to check.
+    #[arg(long, default_value = DEFAULT_RPC)]
+    rpc_url: String,
+    /// Program ID to look up.
+    #[arg(long, default_value = DEFAULT_PROGRAM)]
+    program: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum BookFormat {
+    /// Markdown table.
+    Md,
+    /// Plain aligned text.
+    Text,
+}
+
+/// Which end of a heights list is the leftmost column.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+enum HeightOrder {
+    /// Left to right, the poison column last.
+    #[default]
+    Ltr,
+    /// Right to left, the poison column first.
+    Rtl,
+}
+
+impl HeightOrder {
+    /// Convert between this order and left-to-right; reversing is its own inverse.
+    fn arrange(self, mut heights: [i8; solver_core::COLS]) -> [i8; solver_core::COLS] {
+        if self == HeightOrder::Rtl {
+            heights.reverse();
+        }
+        heights
+    }
+}
+
+/// Account ordering presets for the Play instruction.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+enum AccountLayout {
+    /// System program, player, game PDA, fee collector (the deployed program).
+    #[default]
+    V1,
+    /// Player, game PDA, fee collector, with the system program last.
+    V2,
+}
+
+/// Accounts referenced by the Play instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum AccountRole {
+    SystemProgram,
+    Player,
+    Game,
+    FeeCollector,
+}
+
+/// One entry of an account layout: which account goes here and how it is flagged.
+struct AccountSpec {
+    role: AccountRole,
+    writable: bool,
+    signer: bool,
+}
+
+impl AccountLayout {
+    fn specs(self) -> Vec<AccountSpec> {
+        use AccountRole::*;
+        let spec = |role, writable, signer| AccountSpec {
+            role,
+            writable,
+            signer,
+        };
+        match self {
+            AccountLayout::V1 => vec![
+                spec(SystemProgram, false, false),
+                spec(Player, true, true),
+                spec(Game, true, false),
+                spec(FeeCollector, true, false),
+            ],
+            AccountLayout::V2 => vec![
+                spec(Player, true, true),
+                spec(Game, true, false),
+                spec(FeeCollector, true, false),
+                spec(SystemProgram, false, false),
+            ],
+        }
+    }
 }
 
 #[derive(Serialize)]
 struct SuggestReport {
+    /// Heights of the analyzed board, in the `--state` convention.
+    board: [i8; solver_core::COLS],
+    /// The same board as the text diagram.
+    board_ascii: String,
     winning: bool,
     winning_moves: Vec<(u8, u8)>,
+    /// The winning move picked by `--tiebreak`; `null` when losing.
     recommended: Option<(u8, u8)>,
+    /// The longest-resisting move when losing; `null` when winning.
+    best_defense: Option<(u8, u8)>,
+    /// Own moves needed before a losing position can be handed over, given
+    /// the most helpful replies (see `Solver::distance_to_p`); `null` when
+    /// none can be.
+    distance_to_p: Option<usize>,
+    /// Legal moves best first: winners by `--tiebreak`, then the longest
+    /// defenses. Cut to `--top` entries when given.
+    ranked_moves: Vec<(u8, u8)>,
+    /// Present only with `--timings`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timings: Option<Timings>,
+}
+
+/// Wall-clock time spent in each phase of `suggest`, in milliseconds.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+struct Timings {
+    /// Parsing, loading or fetching the board.
+    resolve_ms: f64,
+    /// Evaluating it.
+    solve_ms: f64,
+}
+
+impl Timings {
+    fn new(resolve: Duration, solve: Duration) -> Self {
+        Self {
+            resolve_ms: resolve.as_secs_f64() * 1000.0,
+            solve_ms: solve.as_secs_f64() * 1000.0,
+        }
+    }
+}
+
+fn build_report(solver: &mut Solver, state: BoardState, tiebreak: Tiebreak) -> SuggestReport {
+    let eval = solver.evaluate(state);
+    SuggestReport {
+        board: *state.heights(),
+        board_ascii: state.to_string(),
+        winning: eval.winning,
+        winning_moves: eval
+            .winning_moves
+            .iter()
+            .map(|mv| mv.to_one_indexed())
+            .collect(),
+        recommended: solver
+            .recommend(state, tiebreak)
+            .map(|mv| mv.to_one_indexed()),
+        best_defense: solver.best_defense(state).map(|mv| mv.to_one_indexed()),
+        distance_to_p: Some(solver.distance_to_p(state)).filter(|&d| d != usize::MAX),
+        ranked_moves: solver
+            .ranked_moves(state, tiebreak)
+            .iter()
+            .map(|mv| mv.to_one_indexed())
+            .collect(),
+        timings: None,
+    }
+}
+
+impl SuggestReport {
+    /// The `--oneline` form: verdict, winning moves, then `rec` with the
+    /// recommendation or `def` with the best defense, in algebraic notation.
+    fn oneline(&self) -> String {
+        let algebraic = |(row, col): (u8, u8)| {
+            Move::try_from(DisplayMove::new(row, col))
+                .map(to_algebraic)
+                .unwrap_or_else(|_| "?".to_string())
+        };
+        let mut line = String::from(if self.winning { "WIN" } else { "LOSS" });
+        for &mv in &self.winning_moves {
+            line.push(' ');
+            line.push_str(&algebraic(mv));
+        }
+        if let Some(mv) = self.recommended {
+            line.push_str(&format!(" | rec {}", algebraic(mv)));
+        } else if let Some(mv) = self.best_defense {
+            line.push_str(&format!(" | def {}", algebraic(mv)));
+        }
+        line
+    }
+
+    /// Apply `--top`: keep only the first `top` ranked moves.
+    fn keep_top(&mut self, top: Option<usize>) {
+        if let Some(top) = top {
+            self.ranked_moves.truncate(top);
+        }
+    }
+}
+
+/// [`build_report`] with the solve phase timed; `resolve` is measured by the caller.
+fn build_timed_report(
+    solver: &mut Solver,
+    state: BoardState,
+    tiebreak: Tiebreak,
+    resolve: Duration,
+) -> SuggestReport {
+    let started = Instant::now();
+    let mut report = build_report(solver, state, tiebreak);
+    report.timings = Some(Timings::new(resolve, started.elapsed()));
+    report
+}
+
+/// Opcode for `suggest --opcode`: the recommended move, or the best defense
+/// when losing. `None` once only the poison is left.
+fn suggested_opcode(report: &SuggestReport) -> Option<u8> {
+    let (row, col) = report.recommended.or(report.best_defense)?;
+    Move::try_from(DisplayMove::new(row, col))
+        .ok()
+        .map(opcode::encode)
+}
+
+/// The opponent's outlook after our move has been applied.
+#[derive(Debug)]
+struct ReplyPreview {
+    opponent_losing: bool,
+    best_reply: Option<Move>,
+}
+
+/// Whose move it is from the autoplay loop's point of view.
+#[derive(Debug, PartialEq, Eq)]
+enum Turn {
+    Mine,
+    Opponent,
+    GameOver,
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
     match cli.command {
         Commands::Suggest(args) => handle_suggest(args),
-        Commands::ExportPolicy { output } => {
-            export_policy_json(&output)
-                .with_context(|| format!("failed to export policy to {:?}", output))?;
+        Commands::ExportPolicy {
+            output,
+            resume,
+            key_format,
+            move_format,
+        } => {
+            let bar = ProgressBar::new(0);
+            let progress = |done, total| {
+                bar.set_length(total as u64);
+                bar.set_position(done as u64);
+            };
+            export_policy_json_resumable(
+                &output,
+                resume,
+                key_format,
+                move_format,
+                progress,
+                || false,
+            )
+            .with_context(|| format!("failed to export policy to {:?}", output))?;
+            bar.finish_and_clear();
             println!("Policy written to {:?}", output);
             Ok(())
         }
+        Commands::ExportResponses { output, tiebreak } => {
+            export_response_map(&output, tiebreak)
+                .with_context(|| format!("failed to export responses to {:?}", output))?;
+            println!("Response map written to {:?}", output);
+            Ok(())
+        }
         Commands::Play(args) => handle_play(args),
+        Commands::Autoplay(args) => handle_autoplay(args),
+        Commands::Book(args) => {
+            let book = OpeningBook::generate(args.depth);
+            print!("{}", render_book(&book, args.format));
+            Ok(())
+        }
+        Commands::PlayLocal(args) => {
+            let start = handicap_state(args.handicap.as_deref())?;
+            let stdin = io::stdin();
+            play_local(start, stdin.lock(), &mut io::stdout())?;
+            Ok(())
+        }
+        Commands::Next(args) => {
+            let next = next_move(&mut Solver::new(), &args.history, args.tiebreak)?;
+            for warning in next.blunder_warnings() {
+                println!("{}", warning);
+            }
+            let player = match next.to_move {
+                Player::One => 1,
+                Player::Two => 2,
+            };
+            let outlook = match next.verdict {
+                Verdict::Win => "winning",
+                Verdict::Loss => "losing",
+            };
+            println!("Player {} to move, {} position", player, outlook);
+            match next.mv {
+                None => println!("Only the poison is left."),
+                Some(mv) if next.verdict == Verdict::Win => {
+                    println!("Recommended move: {}", to_algebraic(mv))
+                }
+                Some(mv) => println!("Best defense: {}", to_algebraic(mv)),
+            }
+            Ok(())
+        }
+        Commands::GenVectors(args) => {
+            let vectors = vectors::generate(args.depth);
+            let file = std::fs::File::create(&args.out)
+                .with_context(|| format!("failed to create {}", args.out.display()))?;
+            serde_json::to_writer_pretty(io::BufWriter::new(file), &vectors)?;
+            println!("Wrote {} vectors to {}", vectors.len(), args.out.display());
+            Ok(())
+        }
+        Commands::Simulate(args) => {
+            let stdout = io::stdout();
+            let wins = simulate(&args, &mut stdout.lock())?;
+            if !args.jsonl {
+                println!(
+                    "{} games: player 1 won {}, player 2 won {}",
+                    args.games, wins[0], wins[1]
+                );
+            }
+            Ok(())
+        }
+        Commands::About => {
+            print!("{}", solution_summary());
+            Ok(())
+        }
+        Commands::Cheatsheet => {
+            print!("{}", p_position_cheatsheet());
+            Ok(())
+        }
+        Commands::Widest => {
+            let (count, states) = widest_positions(&mut Solver::new());
+            println!("{} position(s) with {} winning moves:", states.len(), count);
+            for state in states {
+                println!("\n{:?}\n{}", state.heights(), state);
+            }
+            Ok(())
+        }
+        Commands::Ping(args) => {
+            let player = Pubkey::from_str(&args.player)?;
+            let program_id = Pubkey::from_str(&args.program)?;
+            let rpc = RpcClient::new(args.rpc_url.clone());
+            print!("{}", chain::ping(&rpc, &player, &program_id)?);
+            Ok(())
+        }
+        Commands::CheckProgram(args) => {
+            let program_id = Pubkey::from_str(&args.program)?;
+            let rpc = RpcClient::new(args.rpc_url.clone());
+            match chain::check_program(&rpc, &program_id)? {
+                chain::ProgramStatus::Executable => {
+                    println!("{} is a deployed program on {}.", program_id, args.rpc_url);
+                    Ok(())
+                }
+                chain::ProgramStatus::NotExecutable => Err(anyhow!(
+                    "{} exists on {} but is not executable; check the program ID",
+                    program_id,
+                    args.rpc_url
+                )),
+                chain::ProgramStatus::Missing => Err(anyhow!(
+                    "no account at {} on {}; the ID is wrong or the program is not deployed on this cluster",
+                    program_id,
+                    args.rpc_url
+                )),
+            }
+        }
+        Commands::Pdas(args) => {
+            let program_id = Pubkey::from_str(&args.program)?;
+            let text = std::fs::read_to_string(&args.players_file)
+                .with_context(|| format!("failed to read {}", args.players_file.display()))?;
+            let players = chain::parse_players(&text)?;
+            for (player, pda) in chain::derive_game_pdas(&players, &program_id) {
+                println!("{} {}", player, pda);
+            }
+            Ok(())
+        }
     }
 }
 
 fn handle_suggest(args: SuggestArgs) -> Result<()> {
+    #[cfg(feature = "embed-policy")]
+    let mut solver = Solver::embedded();
+    #[cfg(not(feature = "embed-policy"))]
     let mut solver = Solver::new();
-    let state = resolve_state(
-        args.state.as_deref(),
-        args.player.as_deref(),
-        &args.program,
-        &args.rpc_url,
-    )?;
-    let eval = solver.evaluate(state);
-    if args.json {
-        let report = SuggestReport {
-            winning: eval.winning,
-            winning_moves: eval
-                .winning_moves
-                .iter()
-                .map(|mv| mv.to_one_indexed())
-                .collect(),
-            recommended: eval.winning_moves.first().map(|mv| mv.to_one_indexed()),
-        };
+    let started = Instant::now();
+    let state = suggest_state(&args)?;
+    let resolve = started.elapsed();
+    check_shape(&state, args.allow_unreachable)?;
+    let mut report = if args.timings {
+        build_timed_report(&mut solver, state, args.tiebreak, resolve)
+    } else {
+        build_report(&mut solver, state, args.tiebreak)
+    };
+    report.board = args.output_order.arrange(report.board);
+    report.keep_top(args.top);
+    if args.opcode {
+        let opcode = suggested_opcode(&report)
+            .ok_or_else(|| anyhow!("only the poison is left; there is no move to relay"))?;
+        if !report.winning {
+            eprintln!("warning: position is losing; relaying the best defense");
+        }
+        println!("0x{:02X}", opcode);
+        return Ok(());
+    }
+    if args.oneline {
+        println!("{}", report.oneline());
+    } else if args.json {
         println!("{}", serde_json::to_string_pretty(&report)?);
     } else {
         println!("Current board:\n{}", state);
-        println!("Winning position: {}", eval.winning);
-        if eval.winning {
-            let moves: Vec<String> = eval
+        println!("Winning position: {}", report.winning);
+        if report.winning {
+            let moves: Vec<String> = report
                 .winning_moves
                 .iter()
-                .map(|mv| {
-                    let (r, c) = mv.to_one_indexed();
-                    format!("({},{})", r, c)
-                })
+                .map(|(r, c)| format!("({},{})", r, c))
                 .collect();
             println!("Winning moves: {}", moves.join(", "));
-            if let Some(best) = eval.winning_moves.first() {
-                let (r, c) = best.to_one_indexed();
+            if moves.len() == 1 {
+                println!("It is the only winning move, so there is no margin for error.");
+            }
+            if let Some((r, c)) = report.recommended {
                 println!("Recommended move: ({},{})", r, c);
             }
         } else {
             println!("No forced win from this position—play for asymmetry and hope the AI errs.");
+            if let Some((r, c)) = report.best_defense {
+                println!("Best defense: ({},{})", r, c);
+            }
+            match report.distance_to_p {
+                Some(distance) => println!(
+                    "Moves before a losing position can be handed back, if the AI errs: {}",
+                    distance
+                ),
+                None => println!("No line hands the AI a losing position."),
+            }
+            let explanation = solver.explain_loss(state);
+            if !explanation.refutations.is_empty() {
+                println!("Every move loses because:");
+                for (mv, reply) in explanation.refutations {
+                    let (r, c) = mv.to_one_indexed();
+                    let (rr, rc) = reply.to_one_indexed();
+                    println!("  ({},{}) is answered by ({},{})", r, c, rr, rc);
+                }
+            }
+        }
+        if args.top.is_some() {
+            let moves: Vec<String> = report
+                .ranked_moves
+                .iter()
+                .map(|(r, c)| format!("({},{})", r, c))
+                .collect();
+            println!("Top moves: {}", moves.join(", "));
+        }
+        if let Some(timings) = report.timings {
+            println!(
+                "Timings: resolve {:.1} ms, solve {:.1} ms",
+                timings.resolve_ms, timings.solve_ms
+            );
         }
     }
     Ok(())
 }
 
+fn suggest_state(args: &SuggestArgs) -> Result<BoardState> {
+    #[cfg(feature = "vision")]
+    if let Some(path) = &args.image {
+        return vision::load_board(path);
+    }
+    let source = select_source(
+        args.state.as_deref().map(|raw| (raw, args.order)),
+        args.state_file.as_deref(),
+        args.player.as_deref(),
+        &args.program,
+        &args.rpc_url,
+        args.data_offset,
+        handicap_state(args.handicap.as_deref())?,
+    )?;
+    if !args.dump_account {
+        return source.fetch();
+    }
+    let (state, dump) = source.fetch_with_dump()?;
+    match dump {
+        Some(dump) => eprintln!("{}", dump),
+        None => eprintln!("--dump-account only applies when fetching from chain; ignoring it."),
+    }
+    Ok(state)
+}
+
+/// The starting board: the `--handicap` heights if given, else a full board.
+fn handicap_state(raw: Option<&str>) -> Result<BoardState> {
+    let Some(raw) = raw else {
+        return Ok(BoardState::new());
+    };
+    let state = parse_state(raw).context("invalid --handicap")?;
+    check_shape(&state, false).context("invalid --handicap")?;
+    Ok(state)
+}
+
+/// Interactive game against the solver. The human moves first, entering
+/// one-indexed `row,col`; returns the board when the game ends or input runs out.
+fn play_local<R: BufRead, W: Write>(
+    start: BoardState,
+    input: R,
+    out: &mut W,
+) -> Result<BoardState> {
+    let mut strategy = OptimalStrategy::new();
+    let mut state = start;
+    let mut lines = input.lines();
+    loop {
+        writeln!(out, "{}", state)?;
+        if state.is_terminal() {
+            writeln!(out, "Only the poison is left. You lose.")?;
+            return Ok(state);
+        }
+        write!(out, "Your move (row,col): ")?;
+        out.flush()?;
+        let Some(line) = lines.next() else {
+            writeln!(out)?;
+            return Ok(state);
+        };
+        let line = line?;
+        let parsed = line
+            .split_once(',')
+            .and_then(|(r, c)| Some((r.trim().parse().ok()?, c.trim().parse().ok()?)));
+        let mv = match parsed.map(|(r, c)| to_zero_indexed_move(r, c)) {
+            Some(Ok(mv)) if state.legal_moves().contains(&mv) => mv,
+            _ => {
+                writeln!(out, "Not a legal move: {:?}", line.trim())?;
+                continue;
+            }
+        };
+        state = state.apply_move(mv);
+        let Some(reply) = strategy.choose_move(&state, Player::Two) else {
+            writeln!(
+                out,
+                "{}\nOnly the poison is left for the solver. You win!",
+                state
+            )?;
+            return Ok(state);
+        };
+        let (r, c) = reply.to_one_indexed();
+        writeln!(out, "Solver plays ({},{})", r, c)?;
+        state = state.apply_move(reply);
+    }
+}
+
 fn handle_play(args: PlayArgs) -> Result<()> {
     let program_id = Pubkey::from_str(&args.program)?;
     let fee_collector = Pubkey::from_str(FEE_COLLECTOR)?;
@@ -155,10 +848,13 @@ fn handle_play(args: PlayArgs) -> Result<()> {
     let player_key = payer.pubkey();
     let mut solver = Solver::new();
     let state = resolve_state(
-        args.state.as_deref(),
+        args.state.as_deref().map(|raw| (raw, HeightOrder::Ltr)),
+        args.state_file.as_deref(),
         Some(&player_key.to_string()),
         &args.program,
-        &args.rpc_url,
+        &args.rpc_url[0],
+        args.data_offset,
+        BoardState::new(),
     )?;
     let eval = solver.evaluate(state);
 
@@ -171,56 +867,503 @@ fn handle_play(args: PlayArgs) -> Result<()> {
             .ok_or_else(|| anyhow!("position is losing; specify --row/--col to move anyway"))?
     };
 
-    let (row1, col1) = chosen_move.to_one_indexed();
-    let opcode = ((row1 & 0xF) << 4) | (col1 & 0xF);
-    let (game_pda, _) = Pubkey::find_program_address(&[player_key.as_ref()], &program_id);
+    let reply = args
+        .show_reply
+        .then(|| preview_reply(&mut solver, state, chosen_move));
 
-    let instruction = Instruction::new_with_bytes(
+    let shown = DisplayMove::from(chosen_move);
+    let opcode = opcode::encode(chosen_move);
+    let (instruction, game_pda) = play_instruction(
         program_id,
-        &[opcode],
-        vec![
-            AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
-            AccountMeta::new(player_key, true),
-            AccountMeta::new(game_pda, false),
-            AccountMeta::new(fee_collector, false),
-        ],
+        player_key,
+        fee_collector,
+        args.account_layout,
+        !args.fee_collector_readonly,
+        chosen_move,
     );
 
     if args.dry_run {
         println!(
-            "Dry run: would send move ({},{}) with opcode 0x{:02X}",
-            row1, col1, opcode
+            "Dry run: would send move {} with opcode 0x{:02X}",
+            shown, opcode
         );
         println!("Accounts: player={}, game={} (PDA)", player_key, game_pda);
+        if let Some(reply) = &reply {
+            print_reply(reply);
+        }
         return Ok(());
     }
 
+    if !args.yes && io::stdin().is_terminal() {
+        let stdin = io::stdin();
+        if !confirm_submit(shown, &mut stdin.lock(), &mut io::stdout())? {
+            println!("Not submitted.");
+            return Ok(());
+        }
+    }
+
+    let rpcs: Vec<Arc<RpcClient>> = args
+        .rpc_url
+        .iter()
+        .map(|url| {
+            Arc::new(RpcClient::new_with_commitment(
+                url.clone(),
+                CommitmentConfig::confirmed(),
+            ))
+        })
+        .collect();
+    let tx = sign_instruction(&rpcs[0], &payer, instruction)?;
+    let submitters: Vec<Arc<dyn chain::Submitter>> = rpcs
+        .into_iter()
+        .map(|rpc| rpc as Arc<dyn chain::Submitter>)
+        .collect();
+    let sig = chain::race_submit(&submitters, &tx)?;
+    println!("Submitted move {}. Signature: {}", shown, sig);
+    if let Some(reply) = &reply {
+        print_reply(reply);
+    }
+    Ok(())
+}
+
+/// Ask whether to send `shown`; only an explicit yes counts, so an empty line
+/// or end of input declines.
+fn confirm_submit<R: BufRead, W: Write>(
+    shown: DisplayMove,
+    input: &mut R,
+    out: &mut W,
+) -> Result<bool> {
+    write!(out, "Submit move {} spending a fee? [y/N] ", shown)?;
+    out.flush()?;
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+    Ok(matches!(
+        answer.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
+
+fn handle_autoplay(args: AutoplayArgs) -> Result<()> {
+    let program_id = Pubkey::from_str(&args.program)?;
+    let fee_collector = Pubkey::from_str(FEE_COLLECTOR)?;
+    let payer = read_keypair_file(&args.wallet)
+        .map_err(|err| anyhow!("failed to read keypair {}: {}", args.wallet.display(), err))?;
+    let player_key = payer.pubkey();
     let rpc = RpcClient::new_with_commitment(args.rpc_url.clone(), CommitmentConfig::confirmed());
+    let mut strategy = OptimalStrategy::new();
+    let mut last_move = None;
+
+    loop {
+        // Read at the same commitment we submit at, so our own move shows up
+        // as soon as it is confirmed.
+        let (state, _) = read_game_account(
+            &rpc,
+            &player_key,
+            &program_id,
+            args.data_offset,
+            BoardState::new(),
+        )?;
+        match detect_turn(state, last_move)? {
+            Turn::GameOver => {
+                println!("Game over. Final board:\n{}", state);
+                return Ok(());
+            }
+            Turn::Opponent => thread::sleep(Duration::from_secs(args.poll_secs)),
+            Turn::Mine => {
+                let mv = strategy
+                    .choose_move(&state, Player::One)
+                    .ok_or_else(|| anyhow!("no legal move besides the poison"))?;
+                let (instruction, _) = play_instruction(
+                    program_id,
+                    player_key,
+                    fee_collector,
+                    args.account_layout,
+                    !args.fee_collector_readonly,
+                    mv,
+                );
+                let sig = submit_instruction(&rpc, &payer, instruction)?;
+                let (r, c) = mv.to_one_indexed();
+                println!("Submitted move ({},{}). Signature: {}", r, c, sig);
+                last_move = Some((state, state.apply_move(mv)));
+            }
+        }
+    }
+}
+
+/// Where a game stands after [`next_move`] replays its history.
+#[derive(Debug, PartialEq, Eq)]
+struct NextMove {
+    state: BoardState,
+    to_move: Player,
+    verdict: Verdict,
+    /// The `--tiebreak` pick when winning, the best defense when losing, and
+    /// `None` once only the poison is left.
+    mv: Option<Move>,
+    /// Plies in the history that threw away a win.
+    blunders: Vec<Blunder>,
+}
+
+impl NextMove {
+    /// One warning line per blunder in the history.
+    fn blunder_warnings(&self) -> Vec<String> {
+        self.blunders
+            .iter()
+            .map(|blunder| {
+                let player = match blunder.mover {
+                    Player::One => 1,
+                    Player::Two => 2,
+                };
+                format!(
+                    "Warning: move {} ({}) by player {} threw away a win; {} kept it.",
+                    blunder.ply + 1,
+                    to_algebraic(blunder.played),
+                    player,
+                    to_algebraic(blunder.winning_move)
+                )
+            })
+            .collect()
+    }
+}
+
+/// Replay `history` (algebraic moves separated by whitespace) from the full
+/// board and pick the next move for whoever is on turn.
+fn next_move(solver: &mut Solver, history: &str, tiebreak: Tiebreak) -> Result<NextMove> {
+    let mut state = BoardState::new();
+    let mut moves = Vec::new();
+    for raw in history.split_whitespace() {
+        let plies = moves.len() + 1;
+        let mv = parse_algebraic(raw)
+            .ok_or_else(|| anyhow!("move {} ({:?}) is not in algebraic notation", plies, raw))?;
+        if !state.legal_moves().contains(&mv) {
+            return Err(anyhow!("move {} ({}) is not legal there", plies, raw));
+        }
+        state = state.apply_move(mv);
+        moves.push(mv);
+    }
+    Ok(NextMove {
+        state,
+        to_move: match moves.len() % 2 {
+            0 => Player::One,
+            _ => Player::Two,
+        },
+        verdict: solver.verdict(state),
+        mv: solver
+            .recommend(state, tiebreak)
+            .or_else(|| solver.best_defense(state)),
+        blunders: solver.analyze_game(&moves),
+    })
+}
+
+/// One line of `simulate --jsonl`.
+#[derive(Serialize)]
+struct SimulatedGame {
+    /// Added to `--seed` to get this game's seed.
+    seed_offset: usize,
+    /// Moves in algebraic notation, starting with player 1.
+    moves: Vec<String>,
+    winner: Option<Player>,
+}
+
+/// Play `args.games` seeded random self-play games, writing each as a JSON
+/// line to `out` as soon as it ends when `args.jsonl` is set. Returns the
+/// wins for player 1 and player 2.
+fn simulate<W: Write>(args: &SimulateArgs, out: &mut W) -> Result<[usize; 2]> {
+    let mut wins = [0; 2];
+    for offset in 0..args.games {
+        let seed = args.seed.wrapping_add(offset as u64);
+        let record = self_play(&mut RandomStrategy::new(seed));
+        match record.result {
+            Some(Player::One) => wins[0] += 1,
+            Some(Player::Two) => wins[1] += 1,
+            None => {}
+        }
+        if args.jsonl {
+            let line = SimulatedGame {
+                seed_offset: offset,
+                moves: record.moves.iter().map(|&mv| to_algebraic(mv)).collect(),
+                winner: record.result,
+            };
+            writeln!(out, "{}", serde_json::to_string(&line)?)?;
+            out.flush()?;
+        }
+    }
+    Ok(wins)
+}
+
+/// The largest number of winning moves any reachable position has, and every
+/// position with that many, in [`BoardState`] order.
+fn widest_positions(solver: &mut Solver) -> (usize, Vec<BoardState>) {
+    let mut states = enumerate_states();
+    states.sort();
+    let mut best = 0;
+    let mut widest = Vec::new();
+    for state in states {
+        let count = solver
+            .analyze_moves(state)
+            .iter()
+            .filter(|analysis| analysis.winning)
+            .count();
+        if count > best {
+            best = count;
+            widest.clear();
+        }
+        if count == best && count > 0 {
+            widest.push(state);
+        }
+    }
+    (best, widest)
+}
+
+fn render_book(book: &OpeningBook, format: BookFormat) -> String {
+    let mut out = String::new();
+    if format == BookFormat::Md {
+        out.push_str("| Line | Heights | Verdict | Recommended |\n");
+        out.push_str("|------|---------|---------|-------------|\n");
+    }
+    for entry in &book.entries {
+        let line = if entry.line.is_empty() {
+            "(start)".to_string()
+        } else {
+            line_to_algebraic(&entry.line)
+        };
+        let verdict = if entry.winning { "win" } else { "loss" };
+        let recommended = if entry.recommended.is_empty() {
+            "-".to_string()
+        } else {
+            line_to_algebraic(&entry.recommended)
+        };
+        let heights = format!("{:?}", entry.state.heights());
+        match format {
+            BookFormat::Md => out.push_str(&format!(
+                "| {} | {} | {} | {} |\n",
+                line, heights, verdict, recommended
+            )),
+            BookFormat::Text => out.push_str(&format!(
+                "{:<12} {:<34} {:<5} {}\n",
+                line, heights, verdict, recommended
+            )),
+        }
+    }
+    out
+}
+
+/// Decide whose turn it is. `last_move` is the board before and after our
+/// previous move. While the chain still shows either one (a read can lag
+/// behind the submission), the opponent is to move; it is our turn again only
+/// once the board is one opponent move past ours. Any other board is an error.
+///
+/// Before our first move there is no history to go on, so the side to move is
+/// read off the board with [`BoardState::side_to_move`]: we are assumed to have
+/// opened, and every earlier bite to have left a visible corner.
+fn detect_turn(current: BoardState, last_move: Option<(BoardState, BoardState)>) -> Result<Turn> {
+    if current.is_terminal() {
+        return Ok(Turn::GameOver);
+    }
+    let Some((before, after)) = last_move else {
+        return Ok(match current.side_to_move() {
+            Player::One => Turn::Mine,
+            Player::Two => Turn::Opponent,
+        });
+    };
+    if current == before || current == after {
+        return Ok(Turn::Opponent);
+    }
+    if after
+        .legal_moves()
+        .into_iter()
+        .any(|mv| after.apply_move(mv) == current)
+    {
+        return Ok(Turn::Mine);
+    }
+    Err(anyhow!(
+        "game account shows {:?}, which is not one move after our last move",
+        current.heights()
+    ))
+}
+
+fn play_instruction(
+    program_id: Pubkey,
+    player: Pubkey,
+    fee_collector: Pubkey,
+    layout: AccountLayout,
+    fee_collector_writable: bool,
+    mv: Move,
+) -> (Instruction, Pubkey) {
+    let game_pda = chain::game_pda(&player, &program_id);
+    let instruction = Instruction::new_with_bytes(
+        program_id,
+        &[opcode::encode(mv)],
+        build_account_metas(
+            layout,
+            player,
+            game_pda,
+            fee_collector,
+            fee_collector_writable,
+        ),
+    );
+    (instruction, game_pda)
+}
+
+fn submit_instruction(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    instruction: Instruction,
+) -> Result<Signature> {
+    let tx = sign_instruction(rpc, payer, instruction)?;
+    Ok(rpc.send_and_confirm_transaction(&tx)?)
+}
+
+fn sign_instruction(
+    rpc: &RpcClient,
+    payer: &Keypair,
+    instruction: Instruction,
+) -> Result<Transaction> {
     let blockhash = rpc.get_latest_blockhash()?;
-    let tx =
-        Transaction::new_signed_with_payer(&[instruction], Some(&player_key), &[&payer], blockhash);
-    let sig = rpc.send_and_confirm_transaction(&tx)?;
-    println!("Submitted move ({},{}). Signature: {}", row1, col1, sig);
-    Ok(())
+    Ok(Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[payer],
+        blockhash,
+    ))
+}
+
+/// Account metas in `layout` order. `fee_collector_writable = false` overrides
+/// the layout's flag for deployments that expect the fee collector read-only.
+fn build_account_metas(
+    layout: AccountLayout,
+    player: Pubkey,
+    game: Pubkey,
+    fee_collector: Pubkey,
+    fee_collector_writable: bool,
+) -> Vec<AccountMeta> {
+    layout
+        .specs()
+        .into_iter()
+        .map(|spec| {
+            let key = match spec.role {
+                AccountRole::SystemProgram => solana_sdk::system_program::id(),
+                AccountRole::Player => player,
+                AccountRole::Game => game,
+                AccountRole::FeeCollector => fee_collector,
+            };
+            let writable = match spec.role {
+                AccountRole::FeeCollector => spec.writable && fee_collector_writable,
+                _ => spec.writable,
+            };
+            if writable {
+                AccountMeta::new(key, spec.signer)
+            } else {
+                AccountMeta::new_readonly(key, spec.signer)
+            }
+        })
+        .collect()
+}
+
+fn preview_reply(solver: &mut Solver, state: BoardState, chosen: Move) -> ReplyPreview {
+    let next = state.apply_move(chosen);
+    let eval = solver.evaluate(next);
+    ReplyPreview {
+        opponent_losing: !eval.winning,
+        best_reply: eval
+            .winning_moves
+            .first()
+            .copied()
+            .or_else(|| solver.best_defense(next)),
+    }
+}
+
+fn print_reply(reply: &ReplyPreview) {
+    match reply.best_reply {
+        Some(mv) => {
+            let (r, c) = mv.to_one_indexed();
+            println!("Opponent's best reply: ({},{})", r, c);
+        }
+        None => println!("Opponent has no legal reply besides the poison."),
+    }
+    if reply.opponent_losing {
+        println!("Opponent faces a losing position.");
+    } else {
+        println!("Opponent can still force a win.");
+    }
 }
 
 fn resolve_state(
-    manual: Option<&str>,
+    manual: Option<(&str, HeightOrder)>,
+    state_file: Option<&Path>,
     player: Option<&str>,
     program: &str,
     rpc_url: &str,
+    data_offset: usize,
+    start: BoardState,
 ) -> Result<BoardState> {
-    if let Some(state_str) = manual {
-        return parse_state(state_str);
+    select_source(
+        manual,
+        state_file,
+        player,
+        program,
+        rpc_url,
+        data_offset,
+        start,
+    )?
+    .fetch()
+}
+
+/// Pick the board source: a manual string wins over a file, which wins over the chain.
+fn select_source(
+    manual: Option<(&str, HeightOrder)>,
+    state_file: Option<&Path>,
+    player: Option<&str>,
+    program: &str,
+    rpc_url: &str,
+    data_offset: usize,
+    start: BoardState,
+) -> Result<Box<dyn StateSource>> {
+    if let Some((raw, order)) = manual {
+        return Ok(Box::new(ManualSource {
+            raw: raw.to_string(),
+            order,
+        }));
+    }
+    if let Some(path) = state_file {
+        return Ok(Box::new(FileSource {
+            path: path.to_path_buf(),
+        }));
     }
-    let player =
-        player.ok_or_else(|| anyhow!("player pubkey is required when --state is not provided"))?;
-    let player_key = Pubkey::from_str(player)?;
-    let program_id = Pubkey::from_str(program)?;
-    fetch_state_from_chain(&player_key, &program_id, rpc_url)
+    let player = player.ok_or_else(|| {
+        anyhow!("player pubkey is required when neither --state nor --state-file is provided")
+    })?;
+    Ok(Box::new(ChainSource {
+        player: Pubkey::from_str(player)?,
+        program_id: Pubkey::from_str(program)?,
+        rpc_url: rpc_url.to_string(),
+        data_offset,
+        start,
+    }))
+}
+
+/// Reject boards the solver should not analyze. Without `allow_unreachable`
+/// the board must be reachable in standard play; with it, any valid staircase
+/// (e.g. one with the poison already eaten) is accepted.
+fn check_shape(state: &BoardState, allow_unreachable: bool) -> Result<()> {
+    if !state.is_valid_staircase() {
+        return Err(anyhow!(
+            "heights {:?} are not a legal staircase",
+            state.heights()
+        ));
+    }
+    if !allow_unreachable && !state.is_reachable() {
+        return Err(anyhow!(
+            "board is not reachable in standard play; pass --allow-unreachable to analyze it anyway"
+        ));
+    }
+    Ok(())
 }
 
 fn parse_state(raw: &str) -> Result<BoardState> {
+    parse_state_with_order(raw, HeightOrder::Ltr)
+}
+
+/// [`parse_state`] for heights listed in `order`.
+fn parse_state_with_order(raw: &str, order: HeightOrder) -> Result<BoardState> {
     let values: Vec<i8> = raw
         .split(',')
         .map(|s| s.trim().parse::<i8>())
@@ -234,49 +1377,29 @@ fn parse_state(raw: &str) -> Result<BoardState> {
     }
     let mut heights = [-1i8; solver_core::COLS];
     heights.copy_from_slice(&values);
-    Ok(BoardState::from_heights(heights))
+    Ok(BoardState::try_from_heights(order.arrange(heights))?)
 }
 
-fn fetch_state_from_chain(
+/// The player's game account as seen by `reader`: the board, or `start` if
+/// no game has started, and the raw bytes it was decoded from.
+fn read_game_account(
+    reader: &impl AccountReader,
     player: &Pubkey,
     program_id: &Pubkey,
-    rpc_url: &str,
-) -> Result<BoardState> {
-    let rpc = RpcClient::new(rpc_url.to_string());
-    let (game_pda, _) = Pubkey::find_program_address(&[player.as_ref()], program_id);
-    let data = match rpc.get_account_data(&game_pda) {
-        Ok(data) => data,
-        Err(err) => {
-            if account_missing(&err) {
-                return Ok(BoardState::new());
-            }
-            return Err(err.into());
-        }
+    data_offset: usize,
+    start: BoardState,
+) -> Result<(BoardState, AccountDump)> {
+    let address = chain::game_pda(player, program_id);
+    let data = reader.read_account(&address)?;
+    let state = match &data {
+        Some(bytes) => decode_board_at(bytes, data_offset)?,
+        None => start,
     };
-
-    let mut heights = [-1i8; solver_core::COLS];
-    for col in 0..solver_core::COLS {
-        let mask = 1u8 << (7 - col);
-        for row in 0..solver_core::ROWS {
-            if data.get(row).copied().unwrap_or_default() & mask != 0 {
-                heights[col] = row as i8;
-            }
-        }
-    }
-    Ok(BoardState::from_heights(heights))
+    Ok((state, AccountDump { address, data }))
 }
 
 fn to_zero_indexed_move(row: u8, col: u8) -> Result<Move> {
-    if !(1..=solver_core::ROWS as u8).contains(&row) {
-        return Err(anyhow!("row must be between 1 and {}", solver_core::ROWS));
-    }
-    if !(1..=solver_core::COLS as u8).contains(&col) {
-        return Err(anyhow!(
-            "column must be between 1 and {}",
-            solver_core::COLS
-        ));
-    }
-    Ok(Move::new(row - 1, col - 1))
+    Ok(Move::try_from(DisplayMove::new(row, col))?)
 }
 
 fn account_missing(error: &ClientError) -> bool {
@@ -317,6 +1440,383 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_parse_state_rejects_non_staircase() {
+        let err = parse_state("0,-1,2,-1,-1,-1,-1,-1")
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            "column 2 height 2 exceeds column 1 height -1 (not a legal staircase)"
+        );
+        assert!(parse_state("0,0,-1,-1,-1,-1,-1,5").is_err());
+    }
+
+    #[test]
+    fn test_parse_state_rtl_reverses_heights() {
+        let raw = "-1,-1,-1,-1,-1,-1,0,1";
+        let state = parse_state_with_order(raw, HeightOrder::Rtl).unwrap();
+        assert_eq!(state.heights(), &[1, 0, -1, -1, -1, -1, -1, -1]);
+        assert_eq!(
+            HeightOrder::Rtl.arrange(*state.heights()),
+            [-1, -1, -1, -1, -1, -1, 0, 1]
+        );
+        // Read left to right, the same heights are not a staircase.
+        assert!(parse_state_with_order(raw, HeightOrder::Ltr).is_err());
+    }
+
+    #[test]
+    fn test_preview_reply_after_opening() {
+        let mut solver = Solver::new();
+        let reply = preview_reply(&mut solver, BoardState::new(), Move::new(0, 1));
+        assert!(reply.opponent_losing);
+        let after = BoardState::new().apply_move(Move::new(0, 1));
+        assert_eq!(reply.best_reply, solver.best_defense(after));
+
+        // Here the first legal reply is not the longest defense.
+        let state = BoardState::from_heights([1, 1, 1, 1, 1, 1, 1, -1]);
+        let reply = preview_reply(&mut solver, state, Move::new(2, 2));
+        assert!(reply.opponent_losing);
+        assert_eq!(reply.best_reply, Some(Move::new(3, 1)));
+    }
+
+    #[test]
+    fn test_suggested_opcode_matches_play_instruction() {
+        let mut solver = Solver::new();
+        let report = build_report(&mut solver, BoardState::new(), Tiebreak::First);
+        let (instruction, _) = play_instruction(
+            Pubkey::from_str(DEFAULT_PROGRAM).unwrap(),
+            Pubkey::new_unique(),
+            Pubkey::from_str(FEE_COLLECTOR).unwrap(),
+            AccountLayout::V1,
+            true,
+            Move::new(0, 1),
+        );
+        assert_eq!(suggested_opcode(&report), Some(0x12));
+        assert_eq!(instruction.data, vec![0x12]);
+
+        let losing = BoardState::new().apply_move(Move::new(0, 1));
+        let report = build_report(&mut solver, losing, Tiebreak::First);
+        let (row, col) = report.best_defense.unwrap();
+        assert_eq!(suggested_opcode(&report), Some((row << 4) | col));
+
+        let only_poison = BoardState::from_heights([4, 4, 4, 4, 4, 4, 4, 3]);
+        let report = build_report(&mut solver, only_poison, Tiebreak::First);
+        assert_eq!(suggested_opcode(&report), None);
+    }
+
+    #[test]
+    fn test_default_account_layout_matches_v1_order() {
+        let player = Pubkey::new_unique();
+        let game = Pubkey::new_unique();
+        let fee = Pubkey::from_str(FEE_COLLECTOR).unwrap();
+        let metas = build_account_metas(AccountLayout::default(), player, game, fee, true);
+        assert_eq!(
+            metas,
+            vec![
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+                AccountMeta::new(player, true),
+                AccountMeta::new(game, false),
+                AccountMeta::new(fee, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_v2_account_layout_moves_system_program_last() {
+        let player = Pubkey::new_unique();
+        let game = Pubkey::new_unique();
+        let fee = Pubkey::new_unique();
+        let metas = build_account_metas(AccountLayout::V2, player, game, fee, true);
+        assert_eq!(metas[0], AccountMeta::new(player, true));
+        assert_eq!(
+            metas[3],
+            AccountMeta::new_readonly(solana_sdk::system_program::id(), false)
+        );
+    }
+
+    #[test]
+    fn test_fee_collector_readonly_flag() {
+        let player = Pubkey::new_unique();
+        let game = Pubkey::new_unique();
+        let fee = Pubkey::from_str(FEE_COLLECTOR).unwrap();
+        let metas = build_account_metas(AccountLayout::V1, player, game, fee, false);
+        assert_eq!(metas[3], AccountMeta::new_readonly(fee, false));
+        assert_eq!(metas[2], AccountMeta::new(game, false));
+        let metas = build_account_metas(AccountLayout::V2, player, game, fee, false);
+        assert_eq!(metas[2], AccountMeta::new_readonly(fee, false));
+    }
+
+    #[test]
+    fn test_detect_turn() {
+        let start = BoardState::new();
+        assert_eq!(detect_turn(start, None).unwrap(), Turn::Mine);
+        let opened = start.apply_move(Move::new(0, 1));
+        assert_eq!(detect_turn(opened, None).unwrap(), Turn::Opponent);
+        let replied = opened.apply_move(Move::new(1, 0));
+        assert_eq!(detect_turn(replied, None).unwrap(), Turn::Mine);
+
+        let after_mine = start.apply_move(Move::new(0, 1));
+        let last_move = Some((start, after_mine));
+        assert_eq!(detect_turn(after_mine, last_move).unwrap(), Turn::Opponent);
+
+        let after_reply = after_mine.apply_move(Move::new(1, 0));
+        assert_eq!(detect_turn(after_reply, last_move).unwrap(), Turn::Mine);
+
+        let only_poison = BoardState::from_heights([4, 4, 4, 4, 4, 4, 4, 3]);
+        let last_move = Some((only_poison, only_poison));
+        assert_eq!(detect_turn(only_poison, last_move).unwrap(), Turn::GameOver);
+        assert_eq!(detect_turn(only_poison, None).unwrap(), Turn::GameOver);
+    }
+
+    #[test]
+    fn test_detect_turn_waits_out_a_stale_read() {
+        let start = BoardState::new();
+        let after_mine = start.apply_move(Move::new(0, 1));
+        let last_move = Some((start, after_mine));
+        // The chain has not caught up with our move yet.
+        assert_eq!(detect_turn(start, last_move).unwrap(), Turn::Opponent);
+
+        // Two moves past ours is not something the opponent could have done.
+        let skipped = after_mine
+            .apply_move(Move::new(1, 0))
+            .apply_move(Move::new(0, 4));
+        assert!(detect_turn(skipped, last_move).is_err());
+    }
+
+    #[test]
+    fn test_check_shape_allow_unreachable() {
+        let state = parse_state("4,4,4,4,4,4,4,4").unwrap();
+        assert!(check_shape(&state, false).is_err());
+        assert!(check_shape(&state, true).is_ok());
+        let eval = Solver::new().evaluate(state);
+        assert!(!eval.winning);
+        assert!(eval.winning_moves.is_empty());
+
+        let jagged = BoardState::from_heights_unchecked([0, -1, 2, -1, -1, -1, -1, -1]);
+        assert!(check_shape(&jagged, true).is_err());
+    }
+
+    #[test]
+    fn test_render_book_markdown() {
+        let out = render_book(&OpeningBook::generate(1), BookFormat::Md);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "| Line | Heights | Verdict | Recommended |");
+        assert!(lines[1].starts_with("|---"));
+        assert!(lines[2].starts_with("| (start) |"));
+        assert!(lines[2].ends_with("| win | b1 |"));
+        assert!(lines.iter().any(|l| l.starts_with("| b1 |")));
+    }
+
+    #[test]
+    fn test_next_move_after_opening_is_a_reply_for_player_two() {
+        let mut solver = Solver::new();
+        let next = next_move(&mut solver, "b1", Tiebreak::First).unwrap();
+        assert_eq!(next.state, BoardState::new().apply_move(Move::new(0, 1)));
+        assert_eq!(next.to_move, Player::Two);
+        assert_eq!(next.verdict, Verdict::Loss);
+        let reply = next.mv.unwrap();
+        assert!(next.state.legal_moves().contains(&reply));
+
+        let fresh = next_move(&mut solver, "", Tiebreak::First).unwrap();
+        assert_eq!(fresh.to_move, Player::One);
+        assert_eq!(fresh.mv, Some(Move::new(0, 1)));
+    }
+
+    #[test]
+    fn test_next_move_warns_about_blunders() {
+        let mut solver = Solver::new();
+        let next = next_move(&mut solver, "a1", Tiebreak::First).unwrap();
+        assert_eq!(
+            next.blunder_warnings(),
+            vec!["Warning: move 1 (a1) by player 1 threw away a win; b1 kept it.".to_string()]
+        );
+        let clean = next_move(&mut solver, "b1 a2", Tiebreak::First).unwrap();
+        assert!(clean.blunder_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_next_move_rejects_bad_history() {
+        let mut solver = Solver::new();
+        let err = next_move(&mut solver, "b1 a1", Tiebreak::First).unwrap_err();
+        assert!(err.to_string().contains("move 2"));
+        assert!(next_move(&mut solver, "b1 zz", Tiebreak::First).is_err());
+        assert!(next_move(&mut solver, "h5", Tiebreak::First).is_err());
+    }
+
+    #[test]
+    fn test_widest_positions_match_a_brute_force_scan() {
+        let mut solver = Solver::new();
+        let (count, states) = widest_positions(&mut solver);
+        let brute = enumerate_states()
+            .into_iter()
+            .map(|state| solver.evaluate(state).winning_moves.len())
+            .max()
+            .unwrap();
+        assert_eq!(count, brute);
+        assert!(!states.is_empty());
+        for state in states {
+            assert_eq!(solver.evaluate(state).winning_moves.len(), count);
+        }
+    }
+
+    #[test]
+    fn test_simulate_jsonl_writes_one_line_per_game() {
+        let args = SimulateArgs {
+            games: 5,
+            seed: 42,
+            jsonl: true,
+        };
+        let mut out = Vec::new();
+        let wins = simulate(&args, &mut out).unwrap();
+        assert_eq!(wins[0] + wins[1], 5);
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text.lines().count(), 5);
+        for line in text.lines() {
+            let value: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert!(value.get("moves").is_some());
+        }
+
+        let quiet = SimulateArgs {
+            jsonl: false,
+            ..args
+        };
+        let mut out = Vec::new();
+        assert_eq!(simulate(&quiet, &mut out).unwrap(), wins);
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_handicap_state() {
+        assert_eq!(handicap_state(None).unwrap(), BoardState::new());
+        let state = handicap_state(Some("1,0,-1,-1,-1,-1,-1,-1")).unwrap();
+        assert_eq!(state.heights(), &[1, 0, -1, -1, -1, -1, -1, -1]);
+        assert!(handicap_state(Some("-1,0,-1,-1,-1,-1,-1,-1")).is_err());
+    }
+
+    #[test]
+    fn test_play_local_starts_from_handicap() {
+        let start = handicap_state(Some("2,2,1,-1,-1,-1,-1,-1")).unwrap();
+        let mut out = Vec::new();
+        let end = play_local(start, io::empty(), &mut out).unwrap();
+        assert_eq!(end, start);
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with(&start.to_string()));
+    }
+
+    #[test]
+    fn test_play_local_solver_replies() {
+        let start = handicap_state(Some("0,0,-1,-1,-1,-1,-1,-1")).unwrap();
+        let mut out = Vec::new();
+        let end = play_local(start, "9,9\n1,3\n".as_bytes(), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("Not a legal move: \"9,9\""));
+        assert!(out.contains("Solver plays"));
+        assert_eq!(end.heights()[2], 0);
+    }
+
+    #[test]
+    fn test_build_report_winning() {
+        let mut solver = Solver::new();
+        let report = build_report(&mut solver, BoardState::new(), Tiebreak::First);
+        assert!(report.winning);
+        assert_eq!(report.recommended, Some((1, 2)));
+        assert_eq!(report.best_defense, None);
+        assert_eq!(report.distance_to_p, Some(0));
+    }
+
+    #[test]
+    fn test_confirm_submit_needs_an_explicit_yes() {
+        let shown = DisplayMove::new(1, 2);
+        for (input, expected) in [
+            ("y\n", true),
+            ("YES\n", true),
+            ("n\n", false),
+            ("\n", false),
+            ("", false),
+            ("sure\n", false),
+        ] {
+            let mut out = Vec::new();
+            let confirmed = confirm_submit(shown, &mut input.as_bytes(), &mut out).unwrap();
+            assert_eq!(confirmed, expected, "input {:?}", input);
+            assert_eq!(
+                String::from_utf8(out).unwrap(),
+                "Submit move (1,2) spending a fee? [y/N] "
+            );
+        }
+    }
+
+    #[test]
+    fn test_oneline_report() {
+        let mut solver = Solver::new();
+        let report = build_report(&mut solver, BoardState::new(), Tiebreak::First);
+        assert!(report.oneline().starts_with("WIN"));
+        assert_eq!(report.oneline(), "WIN b1 | rec b1");
+
+        let losing = BoardState::new().apply_move(Move::new(0, 1));
+        let report = build_report(&mut solver, losing, Tiebreak::First);
+        let line = report.oneline();
+        assert!(line.starts_with("LOSS | def "), "{}", line);
+
+        let only_poison = BoardState::from_heights([4, 4, 4, 4, 4, 4, 4, 3]);
+        let report = build_report(&mut solver, only_poison, Tiebreak::First);
+        assert_eq!(report.oneline(), "LOSS");
+    }
+
+    #[test]
+    fn test_top_one_is_the_recommended_move() {
+        let mut solver = Solver::new();
+        let mut report = build_report(&mut solver, BoardState::new(), Tiebreak::Fastest);
+        assert_eq!(
+            report.ranked_moves.len(),
+            BoardState::new().legal_moves().len()
+        );
+        report.keep_top(Some(1));
+        assert_eq!(report.ranked_moves, vec![report.recommended.unwrap()]);
+
+        let losing = BoardState::new().apply_move(Move::new(0, 1));
+        let mut report = build_report(&mut solver, losing, Tiebreak::First);
+        report.keep_top(Some(1));
+        assert_eq!(report.ranked_moves, vec![report.best_defense.unwrap()]);
+        report.keep_top(None);
+        assert_eq!(report.ranked_moves.len(), 1);
+    }
+
+    #[test]
+    fn test_build_report_includes_board() {
+        let state = BoardState::from_heights([1, 0, -1, -1, -1, -1, -1, -1]);
+        let report = build_report(&mut Solver::new(), state, Tiebreak::First);
+        assert_eq!(&report.board, state.heights());
+        assert_eq!(BoardState::from_diagram(&report.board_ascii), Some(state));
+    }
+
+    #[test]
+    fn test_build_timed_report_has_timings() {
+        let state = BoardState::new();
+        let plain = build_report(&mut Solver::new(), state, Tiebreak::First);
+        let json = serde_json::to_value(&plain).unwrap();
+        assert!(json.get("timings").is_none());
+
+        let resolve = Duration::from_millis(3);
+        let report = build_timed_report(&mut Solver::new(), state, Tiebreak::First, resolve);
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["timings"]["resolve_ms"].as_f64(), Some(3.0));
+        assert!(json["timings"]["solve_ms"]
+            .as_f64()
+            .is_some_and(|ms| ms >= 0.0));
+    }
+
+    #[test]
+    fn test_build_report_losing() {
+        let mut solver = Solver::new();
+        let state = BoardState::new().apply_move(Move::new(0, 1));
+        let report = build_report(&mut solver, state, Tiebreak::Fastest);
+        assert_eq!(report.distance_to_p, Some(1));
+        assert!(!report.winning);
+        assert_eq!(report.recommended, None);
+        assert!(report.best_defense.is_some());
+    }
+
     #[test]
     fn test_to_zero_indexed_move_valid() {
         let result = to_zero_indexed_move(1, 1);
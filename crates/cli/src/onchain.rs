@@ -0,0 +1,98 @@
+//! The game account's board layout: one byte per row, top row first, with bit
+//! `7 - col` set where the candy has been eaten.
+
+use anyhow::{anyhow, Result};
+use solver_core::{BoardState, COLS, ROWS};
+
+/// The game-account bytes for `state`.
+pub fn encode_board(state: &BoardState) -> [u8; ROWS] {
+    let mut data = [0u8; ROWS];
+    for (col, &height) in state.heights().iter().enumerate() {
+        for row in data.iter_mut().take((height + 1) as usize) {
+            *row |= 1 << (7 - col);
+        }
+    }
+    data
+}
+
+/// Inverse of [`encode_board`]. Only the first [`ROWS`] bytes are read; it is
+/// an error if there are fewer, or if the eaten cells do not form a staircase
+/// anchored at the top-left corner.
+pub fn decode_board(data: &[u8]) -> Result<BoardState> {
+    let rows = data.get(..ROWS).ok_or_else(|| {
+        anyhow!(
+            "game account has {} bytes, expected at least {}",
+            data.len(),
+            ROWS
+        )
+    })?;
+    let mut heights = [-1i8; COLS];
+    for (col, height) in heights.iter_mut().enumerate() {
+        let mask = 1u8 << (7 - col);
+        let eaten = rows.iter().take_while(|&&row| row & mask != 0).count();
+        if rows[eaten..].iter().any(|&row| row & mask != 0) {
+            return Err(anyhow!("column {} has a gap in its eaten cells", col + 1));
+        }
+        *height = eaten as i8 - 1;
+    }
+    BoardState::try_from_heights(heights)
+        .map_err(|err| anyhow!("eaten cells {:?} do not form a staircase: {}", heights, err))
+}
+
+/// [`decode_board`] for account layouts that put `offset` header bytes, such
+/// as a version or turn counter, before the row bytes.
+pub fn decode_board_at(data: &[u8], offset: usize) -> Result<BoardState> {
+    let rows = data.get(offset..).ok_or_else(|| {
+        anyhow!(
+            "game account has {} bytes, shorter than the {}-byte header",
+            data.len(),
+            offset
+        )
+    })?;
+    decode_board(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solver_core::enumerate_states;
+
+    #[test]
+    fn test_decode_inverts_encode_for_every_reachable_state() {
+        for state in enumerate_states() {
+            assert_eq!(decode_board(&encode_board(&state)).unwrap(), state);
+        }
+        let eaten = BoardState::from_heights([4; COLS]);
+        assert_eq!(decode_board(&encode_board(&eaten)).unwrap(), eaten);
+    }
+
+    #[test]
+    fn test_decode_skips_header_bytes() {
+        let state = BoardState::from_heights([1, 0, -1, -1, -1, -1, -1, -1]);
+        let mut blob = vec![0x02];
+        blob.extend_from_slice(&encode_board(&state));
+        assert_eq!(decode_board_at(&blob, 1).unwrap(), state);
+        assert_eq!(
+            decode_board_at(&blob[1..], 0).unwrap(),
+            decode_board(&blob[1..]).unwrap()
+        );
+        assert!(decode_board_at(&blob, 1 + ROWS + 1).is_err());
+        assert!(decode_board_at(&blob[..ROWS], 1).is_err());
+    }
+
+    #[test]
+    fn test_decode_rejects_malformed_accounts() {
+        assert!(decode_board(&[0; ROWS - 1]).is_err());
+        // Column 1 eaten in row 2 but not row 1.
+        assert!(decode_board(&[0, 0x80, 0, 0, 0]).is_err());
+        // Column 2 eaten further down than column 1.
+        assert!(decode_board(&[0xC0, 0x40, 0, 0, 0]).is_err());
+        let mut padded =
+            encode_board(&BoardState::from_heights([1, 0, -1, -1, -1, -1, -1, -1])).to_vec();
+        padded.push(0xFF);
+        assert_eq!(
+            decode_board(&padded).unwrap().heights(),
+            &[1, 0, -1, -1, -1, -1, -1, -1]
+        );
+    }
+}
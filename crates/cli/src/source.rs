@@ -0,0 +1,168 @@
+//! Where a board state comes from: a manual string, a saved file, or the chain.
+
+use crate::{chain, parse_state_with_order, read_game_account, HeightOrder};
+use anyhow::{Context, Result};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use solver_core::BoardState;
+use std::fmt;
+use std::path::PathBuf;
+
+/// A place the CLI can read the current board from.
+pub trait StateSource {
+    fn fetch(&self) -> Result<BoardState>;
+
+    /// [`StateSource::fetch`] that also returns the raw account it read, for
+    /// sources backed by one.
+    fn fetch_with_dump(&self) -> Result<(BoardState, Option<AccountDump>)> {
+        Ok((self.fetch()?, None))
+    }
+}
+
+/// The game account bytes behind a chain fetch, printed for `--dump-account`.
+pub struct AccountDump {
+    pub address: Pubkey,
+    /// `None` if the account does not exist yet.
+    pub data: Option<Vec<u8>>,
+}
+
+impl fmt::Display for AccountDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.data {
+            Some(bytes) => write!(
+                f,
+                "Game account {} ({} bytes):\n{}",
+                self.address,
+                bytes.len(),
+                chain::hex_dump(bytes)
+            ),
+            None => f.write_str("Game account does not exist yet."),
+        }
+    }
+}
+
+/// Heights typed on the command line, e.g. `"0,0,-1,-1,-1,-1,-1,-1"`.
+pub struct ManualSource {
+    pub raw: String,
+    /// Which end of `raw` is the leftmost column.
+    pub order: HeightOrder,
+}
+
+impl StateSource for ManualSource {
+    fn fetch(&self) -> Result<BoardState> {
+        parse_state_with_order(&self.raw, self.order)
+    }
+}
+
+/// A board saved to disk with [`BoardState::save`].
+pub struct FileSource {
+    pub path: PathBuf,
+}
+
+impl StateSource for FileSource {
+    fn fetch(&self) -> Result<BoardState> {
+        BoardState::load(&self.path)
+            .with_context(|| format!("failed to load board file {}", self.path.display()))
+    }
+}
+
+/// The player's game PDA on-chain.
+pub struct ChainSource {
+    pub player: Pubkey,
+    pub program_id: Pubkey,
+    pub rpc_url: String,
+    /// Header bytes before the row bytes in the game account.
+    pub data_offset: usize,
+    /// Board reported when the player has no game account yet.
+    pub start: BoardState,
+}
+
+impl StateSource for ChainSource {
+    fn fetch(&self) -> Result<BoardState> {
+        Ok(self.fetch_with_dump()?.0)
+    }
+
+    fn fetch_with_dump(&self) -> Result<(BoardState, Option<AccountDump>)> {
+        let rpc = RpcClient::new(self.rpc_url.clone());
+        let (state, dump) = read_game_account(
+            &rpc,
+            &self.player,
+            &self.program_id,
+            self.data_offset,
+            self.start,
+        )?;
+        Ok((state, Some(dump)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_source() {
+        let source = ManualSource {
+            raw: "1,0,-1,-1,-1,-1,-1,-1".to_string(),
+            order: HeightOrder::Ltr,
+        };
+        let state = source.fetch().unwrap();
+        assert_eq!(state.heights(), &[1, 0, -1, -1, -1, -1, -1, -1]);
+
+        let bad = ManualSource {
+            raw: "1,0".to_string(),
+            order: HeightOrder::Ltr,
+        };
+        assert!(bad.fetch().is_err());
+    }
+
+    struct FakeAccount(Option<Vec<u8>>);
+
+    impl chain::AccountReader for FakeAccount {
+        fn read_account(&self, _address: &Pubkey) -> Result<Option<Vec<u8>>> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[test]
+    fn test_read_game_account_dump() {
+        let player = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let state = BoardState::from_heights([1, 0, -1, -1, -1, -1, -1, -1]);
+        let account = FakeAccount(Some(crate::onchain::encode_board(&state).to_vec()));
+        let (read, dump) =
+            read_game_account(&account, &player, &program_id, 0, BoardState::new()).unwrap();
+        assert_eq!(read, state);
+        assert_eq!(dump.address, chain::game_pda(&player, &program_id));
+        assert!(dump
+            .to_string()
+            .starts_with(&format!("Game account {} (5 bytes):", dump.address)));
+
+        let start = BoardState::from_heights([0, -1, -1, -1, -1, -1, -1, -1]);
+        let (read, dump) =
+            read_game_account(&FakeAccount(None), &player, &program_id, 0, start).unwrap();
+        assert_eq!(read, start);
+        assert_eq!(dump.to_string(), "Game account does not exist yet.");
+
+        let manual = ManualSource {
+            raw: "1,0,-1,-1,-1,-1,-1,-1".to_string(),
+            order: HeightOrder::Ltr,
+        };
+        assert!(manual.fetch_with_dump().unwrap().1.is_none());
+    }
+
+    #[test]
+    fn test_file_source() {
+        let path = std::env::temp_dir().join("chomp_file_source_test.txt");
+        BoardState::from_heights([2, 1, 1, 0, -1, -1, -1, -1])
+            .save(&path)
+            .unwrap();
+        let state = FileSource { path: path.clone() }.fetch().unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(state.heights(), &[2, 1, 1, 0, -1, -1, -1, -1]);
+
+        let missing = FileSource {
+            path: std::env::temp_dir().join("chomp_file_source_missing.txt"),
+        };
+        assert!(missing.fetch().is_err());
+    }
+}
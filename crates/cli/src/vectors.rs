@@ -0,0 +1,91 @@
+//! Reference state transitions for testing the on-chain program.
+
+use crate::onchain::encode_board;
+use serde::Serialize;
+use solver_core::book::OpeningBook;
+use solver_core::{losing_positions_with_candies, opcode, BoardState, COLS, ROWS};
+use std::collections::BTreeSet;
+
+/// Losing endgames with up to this many candies are included alongside the
+/// opening book, so the vectors also cover bites next to the poison.
+const ENDGAME_CANDIES: usize = 4;
+
+/// One transition: a board, an instruction byte, and the board it must produce.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct TestVector {
+    /// Heights before the move, in the `--state` convention.
+    pub initial: [i8; COLS],
+    /// The same board as game-account bytes: one per row, bit `7 - col` set where eaten.
+    pub initial_account: [u8; ROWS],
+    pub opcode: u8,
+    pub expected: [i8; COLS],
+    pub expected_account: [u8; ROWS],
+}
+
+/// Every legal move from every position in the opening book of `depth`
+/// plies and from the small losing endgames, in [`BoardState`] order.
+pub fn generate(depth: usize) -> Vec<TestVector> {
+    let mut states: BTreeSet<BoardState> = OpeningBook::generate(depth)
+        .entries
+        .into_iter()
+        .map(|entry| entry.state)
+        .collect();
+    for candies in 1..=ENDGAME_CANDIES {
+        states.extend(losing_positions_with_candies(candies));
+    }
+    states
+        .into_iter()
+        .flat_map(|state| {
+            state.legal_moves().into_iter().map(move |mv| {
+                let next = state.apply_move(mv);
+                TestVector {
+                    initial: *state.heights(),
+                    initial_account: encode_board(&state),
+                    opcode: opcode::encode(mv),
+                    expected: *next.heights(),
+                    expected_account: encode_board(&next),
+                }
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::onchain::decode_board;
+
+    /// Whether applying the decoded opcode to `initial` gives `expected`, in
+    /// both the heights and the account-byte forms.
+    fn is_consistent(vector: &TestVector) -> bool {
+        let Some(mv) = opcode::decode(vector.opcode) else {
+            return false;
+        };
+        let Ok(initial) = BoardState::try_from_heights(vector.initial) else {
+            return false;
+        };
+        let expected = initial.apply_move(mv);
+        initial.legal_moves().contains(&mv)
+            && decode_board(&vector.initial_account).ok() == Some(initial)
+            && *expected.heights() == vector.expected
+            && decode_board(&vector.expected_account).ok() == Some(expected)
+    }
+
+    #[test]
+    fn test_generated_vectors_are_self_consistent() {
+        let vectors = generate(2);
+        assert!(vectors.iter().all(is_consistent));
+        assert_eq!(vectors[0].initial, [-1; COLS]);
+        assert_eq!(vectors[0].initial_account, [0; ROWS]);
+        let opening = vectors
+            .iter()
+            .find(|v| v.initial == [-1; COLS] && v.opcode == 0x12)
+            .unwrap();
+        assert_eq!(opening.expected, [0, 0, -1, -1, -1, -1, -1, -1]);
+        assert_eq!(opening.expected_account, [0xC0, 0, 0, 0, 0]);
+
+        let mut broken = opening.clone();
+        broken.opcode = 0x13;
+        assert!(!is_consistent(&broken));
+    }
+}
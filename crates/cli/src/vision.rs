@@ -0,0 +1,95 @@
+//! Read a board from an image of the grid (enabled with the `vision` feature).
+//!
+//! Expected format: the image is cropped to the board and split into
+//! `ROWS × COLS` equal cells. A cell whose centre is dark (mean luma below
+//! [`CANDY_THRESHOLD`]) still holds a candy; a light centre means it was eaten.
+//! The poison cell must be dark. Only the middle half of each cell is sampled,
+//! so grid lines and small misalignments do not matter.
+
+use anyhow::{anyhow, Context, Result};
+use image::GrayImage;
+use solver_core::{BoardState, COLS, ROWS};
+use std::path::Path;
+
+/// Mean luma at or above which a cell counts as eaten.
+pub const CANDY_THRESHOLD: u8 = 128;
+
+/// Decode the board pictured in the image file at `path`.
+pub fn load_board(path: &Path) -> Result<BoardState> {
+    let img = image::open(path)
+        .with_context(|| format!("failed to open image {}", path.display()))?
+        .to_luma8();
+    decode_grid(&img)
+}
+
+/// Decode an already-loaded grayscale image of the board.
+pub fn decode_grid(img: &GrayImage) -> Result<BoardState> {
+    let (width, height) = img.dimensions();
+    let (cell_w, cell_h) = (width / COLS as u32, height / ROWS as u32);
+    if cell_w == 0 || cell_h == 0 {
+        return Err(anyhow!(
+            "image is {}x{}, too small for a {}x{} grid",
+            width,
+            height,
+            COLS,
+            ROWS
+        ));
+    }
+
+    let mut cells = [[false; COLS]; ROWS];
+    for (row, cells_row) in cells.iter_mut().enumerate() {
+        for (col, eaten) in cells_row.iter_mut().enumerate() {
+            let x0 = col as u32 * cell_w + cell_w / 4;
+            let y0 = row as u32 * cell_h + cell_h / 4;
+            let (sw, sh) = ((cell_w / 2).max(1), (cell_h / 2).max(1));
+            let mut sum = 0u64;
+            for y in y0..y0 + sh {
+                for x in x0..x0 + sw {
+                    sum += u64::from(img.get_pixel(x, y).0[0]);
+                }
+            }
+            let mean = sum / u64::from(sw * sh);
+            *eaten = mean >= u64::from(CANDY_THRESHOLD);
+        }
+    }
+
+    BoardState::from_cells(&cells)
+        .ok_or_else(|| anyhow!("decoded grid is not a legal staircase with the poison intact"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Luma;
+    use solver_core::Move;
+
+    /// Draw `state` with 20px cells: dark candies, light eaten cells, black grid lines.
+    fn render(state: &BoardState) -> GrayImage {
+        const CELL: u32 = 20;
+        GrayImage::from_fn(CELL * COLS as u32, CELL * ROWS as u32, |x, y| {
+            if x % CELL == 0 || y % CELL == 0 {
+                return Luma([0]);
+            }
+            let (row, col) = ((y / CELL) as i8, (x / CELL) as usize);
+            let eaten = state.heights()[col] >= row;
+            Luma([if eaten { 230 } else { 40 }])
+        })
+    }
+
+    #[test]
+    fn decodes_rendered_grid() {
+        let state = BoardState::new()
+            .apply_move(Move::new(0, 1))
+            .apply_move(Move::new(2, 0));
+        assert_eq!(decode_grid(&render(&state)).unwrap(), state);
+        assert_eq!(
+            decode_grid(&render(&BoardState::new())).unwrap(),
+            BoardState::new()
+        );
+    }
+
+    #[test]
+    fn rejects_tiny_images() {
+        assert!(decode_grid(&GrayImage::new(4, 4)).is_err());
+    }
+}
@@ -0,0 +1,80 @@
+//! With the `embed-policy` feature, solve the board once at build time and
+//! write the table that `solver_core::embedded` includes. The rules are
+//! restated here because a build script cannot depend on its own crate; the
+//! embedded table is checked against the live solver in the crate's tests.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+const ROWS: usize = 5;
+const COLS: usize = 8;
+
+type Heights = [i8; COLS];
+
+/// Uneaten cells other than the poison, column by column from the left, as
+/// in `BoardState::legal_moves`.
+fn legal_moves(heights: &Heights) -> Vec<(usize, usize)> {
+    let mut moves = Vec::new();
+    for (col, &height) in heights.iter().enumerate() {
+        for row in (height + 1) as usize..ROWS {
+            if (row, col) != (ROWS - 1, COLS - 1) {
+                moves.push((row, col));
+            }
+        }
+    }
+    moves
+}
+
+fn apply_move(heights: &Heights, (row, col): (usize, usize)) -> Heights {
+    let mut next = *heights;
+    for height in next.iter_mut().take(col + 1) {
+        *height = (*height).max(row as i8);
+    }
+    next
+}
+
+/// Bitmask (bit `row * COLS + col`) of the winning moves from `heights`.
+fn winning_mask(heights: Heights, memo: &mut HashMap<Heights, u64>) -> u64 {
+    if let Some(&mask) = memo.get(&heights) {
+        return mask;
+    }
+    let mut mask = 0;
+    for mv in legal_moves(&heights) {
+        if winning_mask(apply_move(&heights, mv), memo) == 0 {
+            mask |= 1 << (mv.0 * COLS + mv.1);
+        }
+    }
+    memo.insert(heights, mask);
+    mask
+}
+
+/// `BoardState::to_u64`: 3 bits per column holding the eaten count.
+fn pack(heights: &Heights) -> u32 {
+    heights
+        .iter()
+        .enumerate()
+        .map(|(col, &h)| ((h + 1) as u32) << (3 * col))
+        .sum()
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    if env::var_os("CARGO_FEATURE_EMBED_POLICY").is_none() {
+        return;
+    }
+
+    let mut memo = HashMap::new();
+    winning_mask([-1; COLS], &mut memo);
+    let mut records: Vec<(u32, u64)> = memo.iter().map(|(h, &mask)| (pack(h), mask)).collect();
+    records.sort_unstable();
+
+    let mut bytes = Vec::with_capacity(records.len() * 12);
+    for (key, mask) in records {
+        bytes.extend_from_slice(&key.to_le_bytes());
+        bytes.extend_from_slice(&mask.to_le_bytes());
+    }
+    let out = PathBuf::from(env::var_os("OUT_DIR").expect("cargo sets OUT_DIR"));
+    fs::write(out.join("policy.bin"), bytes).expect("failed to write the embedded policy");
+}
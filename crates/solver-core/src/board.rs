@@ -0,0 +1,274 @@
+//! The board and its rules, with nothing beyond `core` and `alloc`, so this
+//! part of the crate also builds without the `std` feature.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Number of rows on the Chomping Glass board.
+pub const ROWS: usize = 5;
+/// Number of columns on the Chomping Glass board.
+pub const COLS: usize = 8;
+
+/// The coordinates of the poison square (zero-indexed).
+pub const POISON: Move = Move {
+    row: (ROWS - 1) as u8,
+    col: (COLS - 1) as u8,
+};
+
+/// Representation of a solver move in zero-indexed board coordinates.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct Move {
+    pub row: u8,
+    pub col: u8,
+}
+
+impl Move {
+    pub const fn new(row: u8, col: u8) -> Self {
+        Self { row, col }
+    }
+
+    pub const fn to_tuple(self) -> (u8, u8) {
+        (self.row, self.col)
+    }
+}
+
+/// Board state encoded as column heights (Ferrers shape).
+///
+/// Ordering is lexicographic over the heights, which gives exporters and
+/// fingerprints a canonical iteration order.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub struct BoardState {
+    pub(crate) heights: [i8; COLS],
+}
+
+impl BoardState {
+    /// Fresh board.
+    pub fn new() -> Self {
+        Self {
+            heights: [-1; COLS],
+        }
+    }
+
+    /// Construct from explicit heights (mostly useful for tests).
+    ///
+    /// Panics if the heights are not a staircase; see
+    /// [`BoardState::try_from_heights`].
+    pub fn from_heights(heights: [i8; COLS]) -> Self {
+        match Self::try_from_heights(heights) {
+            Ok(state) => state,
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+    /// Construct from explicit heights, rejecting any that are out of range
+    /// or increase from one column to the next, since no sequence of bites
+    /// leaves such a shape.
+    pub fn try_from_heights(heights: [i8; COLS]) -> Result<Self, ShapeError> {
+        for (col, &height) in heights.iter().enumerate() {
+            if !(-1..=(ROWS as i8 - 1)).contains(&height) {
+                return Err(ShapeError::OutOfRange { col, height });
+            }
+            if col > 0 && height > heights[col - 1] {
+                return Err(ShapeError::NotStaircase {
+                    col,
+                    height,
+                    left: heights[col - 1],
+                });
+            }
+        }
+        Ok(Self { heights })
+    }
+
+    /// Construct from heights without any checks, for tests that need
+    /// deliberately malformed boards.
+    pub fn from_heights_unchecked(heights: [i8; COLS]) -> Self {
+        Self { heights }
+    }
+
+    pub fn heights(&self) -> &[i8; COLS] {
+        &self.heights
+    }
+
+    /// Whether the heights are in range and non-increasing from left to right,
+    /// i.e. the eaten region is a staircase anchored at the top-left corner.
+    pub fn is_valid_staircase(&self) -> bool {
+        self.heights
+            .iter()
+            .all(|&h| (-1..=(ROWS as i8 - 1)).contains(&h))
+            && self.heights.windows(2).all(|w| w[1] <= w[0])
+    }
+
+    /// Whether standard play can reach this board: a valid staircase that
+    /// still has the poison on it (nobody ever bites the poison as a move).
+    pub fn is_reachable(&self) -> bool {
+        self.is_valid_staircase() && self.heights[POISON.col as usize] < POISON.row as i8
+    }
+
+    /// Return a new state after applying `mv`.
+    /// Eats the candy at (row, col) and all candies above it and to the left.
+    pub fn apply_move(&self, mv: Move) -> Self {
+        let mut next = *self;
+        let target_row = mv.row as i8;
+        // Eat all columns from 0 up to and including mv.col (left and including chosen candy)
+        for col in 0..=mv.col as usize {
+            if target_row > next.heights[col] {
+                next.heights[col] = target_row;
+            }
+        }
+        next
+    }
+
+    /// [`BoardState::apply_move`] that also lists the cells the move eats,
+    /// column by column from the left.
+    pub fn apply_move_detailed(&self, mv: Move) -> (Self, Vec<Move>) {
+        let eaten = (0..=mv.col as usize)
+            .flat_map(|col| {
+                ((self.heights[col] + 1)..=(mv.row as i8))
+                    .map(move |row| Move::new(row as u8, col as u8))
+            })
+            .collect();
+        (self.apply_move(mv), eaten)
+    }
+
+    /// How many cells `mv` eats, without building the successor board: the
+    /// uneaten part of the rectangle above and to the left of it.
+    pub fn eaten_count(&self, mv: Move) -> usize {
+        self.heights[..=mv.col as usize]
+            .iter()
+            .map(|&h| (mv.row as i8 - h).max(0) as usize)
+            .sum()
+    }
+
+    /// Generate every legal candy move from this position.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        self.uneaten_cells().filter(|&mv| mv != POISON).collect()
+    }
+
+    /// Every uneaten cell, column by column from the left.
+    pub(crate) fn uneaten_cells(&self) -> impl Iterator<Item = Move> + '_ {
+        (0..COLS).flat_map(move |col| {
+            ((self.heights[col] + 1)..(ROWS as i8)).map(move |row| Move::new(row as u8, col as u8))
+        })
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        self.legal_moves().is_empty()
+    }
+}
+
+/// Why [`BoardState::try_from_heights`] rejected a set of heights. Columns
+/// are zero-indexed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ShapeError {
+    /// A height outside `-1..ROWS`.
+    OutOfRange { col: usize, height: i8 },
+    /// A column eaten further down than the column to its left.
+    NotStaircase { col: usize, height: i8, left: i8 },
+}
+
+impl fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ShapeError::OutOfRange { col, height } => write!(
+                f,
+                "column {} height {} is outside -1..={}",
+                col,
+                height,
+                ROWS - 1
+            ),
+            ShapeError::NotStaircase { col, height, left } => write!(
+                f,
+                "column {} height {} exceeds column {} height {} (not a legal staircase)",
+                col,
+                height,
+                col - 1,
+                left
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ShapeError {}
+
+impl Default for BoardState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One of the two players; [`Player::One`] makes the opening move.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub enum Player {
+    One,
+    Two,
+}
+
+impl Player {
+    pub fn opponent(self) -> Self {
+        match self {
+            Player::One => Player::Two,
+            Player::Two => Player::One,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opening_move_leaves_a_live_board() {
+        let state = BoardState::new().apply_move(Move::new(0, 1));
+        assert_eq!(state.heights(), &[0, 0, -1, -1, -1, -1, -1, -1]);
+        assert_eq!(state.legal_moves().len(), ROWS * COLS - 3);
+        assert!(!state.is_terminal());
+        assert!(BoardState::from_heights([4, 4, 4, 4, 4, 4, 4, 3]).is_terminal());
+    }
+
+    #[test]
+    fn try_from_heights_rejects_impossible_shapes() {
+        let staircase = [2, 1, 1, 0, -1, -1, -1, -1];
+        assert_eq!(
+            BoardState::try_from_heights(staircase),
+            Ok(BoardState::from_heights_unchecked(staircase))
+        );
+        assert_eq!(
+            BoardState::try_from_heights([0, -1, 2, -1, -1, -1, -1, -1]),
+            Err(ShapeError::NotStaircase {
+                col: 2,
+                height: 2,
+                left: -1
+            })
+        );
+        assert_eq!(
+            BoardState::try_from_heights([5, 0, -1, -1, -1, -1, -1, -1]),
+            Err(ShapeError::OutOfRange { col: 0, height: 5 })
+        );
+        assert_eq!(
+            BoardState::try_from_heights([-1, -1, -1, -1, -1, -1, -1, -2]),
+            Err(ShapeError::OutOfRange { col: 7, height: -2 })
+        );
+    }
+
+    #[test]
+    fn eaten_count_matches_detailed_move() {
+        let state = BoardState::from_heights([2, 1, 1, 0, -1, -1, -1, -1]);
+        for mv in [
+            Move::new(0, 0),
+            Move::new(3, 0),
+            Move::new(2, 3),
+            Move::new(4, 6),
+            Move::new(0, 7),
+        ] {
+            let (next, eaten) = state.apply_move_detailed(mv);
+            assert_eq!(next, state.apply_move(mv));
+            assert_eq!(state.eaten_count(mv), eaten.len());
+        }
+        assert_eq!(state.eaten_count(Move::new(0, 0)), 0);
+        assert_eq!(state.eaten_count(Move::new(2, 3)), 4);
+        assert_eq!(BoardState::new().eaten_count(Move::new(0, 1)), 2);
+    }
+}
@@ -0,0 +1,112 @@
+//! Opening book generated from perfect play.
+
+use crate::{BoardState, Move, Solver};
+
+/// A position in the book together with the line that reaches it.
+#[derive(Clone, Debug)]
+pub struct BookEntry {
+    /// Moves from the empty board, alternating between the two players.
+    pub line: Vec<Move>,
+    pub state: BoardState,
+    pub winning: bool,
+    /// Winning moves for the side to play; empty when the position is lost.
+    pub recommended: Vec<Move>,
+}
+
+/// Every position within a given number of plies of the opening, following
+/// only winning moves for the side that has one and every reply otherwise.
+#[derive(Clone, Debug, Default)]
+pub struct OpeningBook {
+    pub entries: Vec<BookEntry>,
+}
+
+impl OpeningBook {
+    /// Build the book for lines of up to `depth` plies, in breadth-first order.
+    pub fn generate(depth: usize) -> Self {
+        let mut solver = Solver::new();
+        let mut entries = Vec::new();
+        let mut frontier = vec![(Vec::new(), BoardState::new())];
+
+        for ply in 0..=depth {
+            let mut next = Vec::new();
+            for (line, state) in frontier {
+                let eval = solver.evaluate(state);
+                if ply < depth {
+                    let branches = if eval.winning {
+                        eval.winning_moves.clone()
+                    } else {
+                        state.legal_moves()
+                    };
+                    for mv in branches {
+                        let mut longer: Vec<Move> = line.clone();
+                        longer.push(mv);
+                        next.push((longer, state.apply_move(mv)));
+                    }
+                }
+                entries.push(BookEntry {
+                    line,
+                    state,
+                    winning: eval.winning,
+                    recommended: eval.winning_moves,
+                });
+            }
+            frontier = next;
+        }
+
+        Self { entries }
+    }
+}
+
+/// The hand-verified lines from the strategy notes: each move sequence from
+/// the empty board paired with every winning answer, in `legal_moves` order.
+/// Covers the opener and the replies to the AI's first-column and
+/// first-row answers to it.
+pub fn known_opening_book() -> Vec<(Vec<Move>, Vec<Move>)> {
+    let opener = Move::new(0, 1);
+    let mut book = vec![(Vec::new(), vec![opener])];
+    let ai_responses = [
+        (Move::new(1, 0), vec![Move::new(0, 4)]),
+        (Move::new(0, 2), vec![Move::new(3, 1)]),
+        (Move::new(2, 0), vec![Move::new(1, 2), Move::new(0, 3)]),
+        (Move::new(3, 0), vec![Move::new(2, 7)]),
+        (Move::new(4, 0), vec![Move::new(3, 5)]),
+    ];
+    for (ai_move, answers) in ai_responses {
+        book.push((vec![opener, ai_move], answers));
+    }
+    book
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn depth_zero_is_the_opening() {
+        let book = OpeningBook::generate(0);
+        assert_eq!(book.entries.len(), 1);
+        assert_eq!(book.entries[0].recommended, vec![Move::new(0, 1)]);
+    }
+
+    #[test]
+    fn known_opening_book_agrees_with_the_solver() {
+        let mut solver = Solver::new();
+        for (line, answers) in known_opening_book() {
+            let state = line
+                .iter()
+                .fold(BoardState::new(), |state, &mv| state.apply_move(mv));
+            assert_eq!(solver.evaluate(state).winning_moves, answers, "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn depth_two_covers_every_reply_to_the_opener() {
+        let book = OpeningBook::generate(2);
+        let after_opener = BoardState::new().apply_move(Move::new(0, 1));
+        let replies = after_opener.legal_moves().len();
+        assert_eq!(book.entries.len(), 1 + 1 + replies);
+        assert!(book.entries[2..]
+            .iter()
+            .all(|e| e.winning && e.line.len() == 2));
+    }
+}
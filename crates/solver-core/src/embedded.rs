@@ -0,0 +1,95 @@
+//! The full policy table, solved at build time by `build.rs` under the
+//! `embed-policy` feature so lookups need no search.
+
+use crate::{BoardState, Evaluation, Move, COLS, ROWS};
+
+/// Little-endian `(key: u32, winning moves: u64)` records sorted by key. The
+/// key is [`BoardState::to_u64`]; bit `row * COLS + col` marks a winning move.
+static POLICY: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/policy.bin"));
+
+const RECORD_LEN: usize = 12;
+
+fn record(index: usize) -> (u32, u64) {
+    let bytes = &POLICY[index * RECORD_LEN..(index + 1) * RECORD_LEN];
+    let key = u32::from_le_bytes(bytes[..4].try_into().expect("4-byte key"));
+    let mask = u64::from_le_bytes(bytes[4..].try_into().expect("8-byte mask"));
+    (key, mask)
+}
+
+/// Winning moves in `legal_moves` order: column by column, top to bottom.
+fn decode_moves(mask: u64) -> Vec<Move> {
+    (0..COLS)
+        .flat_map(|col| (0..ROWS).map(move |row| (row, col)))
+        .filter(|&(row, col)| mask & (1 << (row * COLS + col)) != 0)
+        .map(|(row, col)| Move::new(row as u8, col as u8))
+        .collect()
+}
+
+fn evaluation(mask: u64) -> Evaluation {
+    Evaluation {
+        winning: mask != 0,
+        winning_moves: decode_moves(mask),
+    }
+}
+
+/// Number of positions in the table.
+pub fn len() -> usize {
+    POLICY.len() / RECORD_LEN
+}
+
+/// The precomputed [`Evaluation`] of `state`, or `None` if it is unreachable.
+pub fn lookup(state: &BoardState) -> Option<Evaluation> {
+    let key = u32::try_from(state.to_u64()).ok()?;
+    let (mut lo, mut hi) = (0, len());
+    while lo < hi {
+        let mid = (lo + hi) / 2;
+        let (found, mask) = record(mid);
+        match found.cmp(&key) {
+            std::cmp::Ordering::Less => lo = mid + 1,
+            std::cmp::Ordering::Greater => hi = mid,
+            std::cmp::Ordering::Equal => return Some(evaluation(mask)),
+        }
+    }
+    None
+}
+
+/// Every position with its evaluation, in key order.
+pub fn entries() -> impl Iterator<Item = (BoardState, Evaluation)> {
+    (0..len()).map(|index| {
+        let (key, mask) = record(index);
+        let state = BoardState::from_u64(key.into()).expect("embedded keys are reachable boards");
+        (state, evaluation(mask))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{enumerate_states, Solver};
+
+    #[test]
+    fn embedded_table_matches_live_solve() {
+        let states = enumerate_states();
+        assert_eq!(len(), states.len());
+        let mut solver = Solver::new();
+        for state in states {
+            let live = solver.evaluate(state);
+            let embedded = lookup(&state).unwrap();
+            assert_eq!(embedded.winning, live.winning);
+            assert_eq!(embedded.winning_moves, live.winning_moves);
+        }
+        let eaten = BoardState::from_heights([4; COLS]);
+        assert!(lookup(&eaten).is_none());
+    }
+
+    #[test]
+    fn embedded_solver_answers_from_cache() {
+        let mut solver = Solver::embedded();
+        assert_eq!(solver.verify_invariants(), Ok(()));
+        assert_eq!(
+            solver.evaluate(BoardState::new()).winning_moves,
+            vec![Move::new(0, 1)]
+        );
+        assert_eq!(solver.stats().nodes_expanded, 0);
+    }
+}
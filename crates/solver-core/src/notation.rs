@@ -0,0 +1,56 @@
+//! Algebraic move notation: a column letter followed by a one-indexed row,
+//! so the opening move `(row 0, col 1)` is written `b1`.
+
+use crate::{Move, COLS, ROWS};
+
+/// Format a zero-indexed move, e.g. `Move::new(0, 1)` → `"b1"`.
+pub fn to_algebraic(mv: Move) -> String {
+    format!("{}{}", (b'a' + mv.col) as char, mv.row + 1)
+}
+
+/// Parse algebraic notation (case-insensitive column letter), rejecting
+/// coordinates outside the board.
+pub fn parse_algebraic(raw: &str) -> Option<Move> {
+    let raw = raw.trim();
+    let mut chars = raw.chars();
+    let letter = chars.next()?.to_ascii_lowercase();
+    if !letter.is_ascii_lowercase() {
+        return None;
+    }
+    let col = letter as u8 - b'a';
+    let row: u8 = chars.as_str().parse().ok()?;
+    if col as usize >= COLS || !(1..=ROWS as u8).contains(&row) {
+        return None;
+    }
+    Some(Move::new(row - 1, col))
+}
+
+/// Format a sequence of moves separated by spaces.
+pub fn line_to_algebraic(moves: &[Move]) -> String {
+    moves
+        .iter()
+        .map(|&mv| to_algebraic(mv))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opening_is_b1() {
+        assert_eq!(to_algebraic(Move::new(0, 1)), "b1");
+        assert_eq!(parse_algebraic("b1"), Some(Move::new(0, 1)));
+        assert_eq!(parse_algebraic(" H5 "), Some(Move::new(4, 7)));
+    }
+
+    #[test]
+    fn rejects_off_board_and_garbage() {
+        assert_eq!(parse_algebraic("i1"), None);
+        assert_eq!(parse_algebraic("a0"), None);
+        assert_eq!(parse_algebraic("a6"), None);
+        assert_eq!(parse_algebraic("11"), None);
+        assert_eq!(parse_algebraic(""), None);
+    }
+}
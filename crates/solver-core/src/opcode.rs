@@ -0,0 +1,42 @@
+//! Single-byte move encoding used by the on-chain program.
+//!
+//! The high nibble holds the one-indexed row and the low nibble the
+//! one-indexed column, so `(1,2)` encodes as `0x12`.
+
+use crate::{DisplayMove, Move};
+
+/// Encode a zero-indexed move as the program's instruction byte.
+pub fn encode(mv: Move) -> u8 {
+    let shown = DisplayMove::from(mv);
+    ((shown.row & 0xF) << 4) | (shown.col & 0xF)
+}
+
+/// Decode an instruction byte, rejecting coordinates outside the board.
+pub fn decode(opcode: u8) -> Option<Move> {
+    Move::try_from(DisplayMove::new(opcode >> 4, opcode & 0xF)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{COLS, ROWS};
+
+    #[test]
+    fn opening_encodes_as_0x12() {
+        assert_eq!(encode(Move::new(0, 1)), 0x12);
+        assert_eq!(decode(0x12), Some(Move::new(0, 1)));
+    }
+
+    #[test]
+    fn round_trips_every_cell_and_rejects_off_board() {
+        for row in 0..ROWS as u8 {
+            for col in 0..COLS as u8 {
+                let mv = Move::new(row, col);
+                assert_eq!(decode(encode(mv)), Some(mv));
+            }
+        }
+        assert_eq!(decode(0x00), None);
+        assert_eq!(decode(0x61), None);
+        assert_eq!(decode(0x19), None);
+    }
+}
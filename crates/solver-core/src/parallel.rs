@@ -0,0 +1,36 @@
+//! Multi-threaded solving behind the `parallel` feature.
+
+use crate::{BoardState, Verdict, POISON};
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// Misère verdicts shared by every worker thread.
+#[derive(Default)]
+struct SharedVerdicts {
+    cache: RwLock<HashMap<BoardState, Verdict>>,
+}
+
+impl SharedVerdicts {
+    fn verdict(&self, state: BoardState) -> Verdict {
+        if let Some(&verdict) = self.cache.read().unwrap().get(&state) {
+            return verdict;
+        }
+        let winning = state
+            .uneaten_cells()
+            .filter(|&mv| mv != POISON)
+            .any(|mv| self.verdict(state.apply_move(mv)) == Verdict::Loss);
+        let verdict = Verdict::from_winning(winning);
+        // Two threads may solve the same position; they always agree.
+        self.cache.write().unwrap().insert(state, verdict);
+        verdict
+    }
+}
+
+pub(crate) fn classify_batch(states: &[BoardState]) -> Vec<Verdict> {
+    let shared = SharedVerdicts::default();
+    states
+        .par_iter()
+        .map(|&state| shared.verdict(state))
+        .collect()
+}
@@ -0,0 +1,197 @@
+//! Complete games in a form that can be saved, shared, and replayed.
+
+use crate::notation::to_algebraic;
+use crate::{BoardState, Move, Player, COLS, ROWS};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// A full game: the board it was played on, every move in order, and the
+/// winner once it is over.
+///
+/// Boards smaller than 5×8 sit in the poison corner of the full board, and
+/// `moves` are zero-indexed on the record's own `board_rows × board_cols`
+/// grid, so the poison is always `(board_rows - 1, board_cols - 1)`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub board_rows: usize,
+    pub board_cols: usize,
+    /// Moves alternating between the players, starting with [`Player::One`].
+    pub moves: Vec<Move>,
+    /// The winner, or `None` while the game is still in progress.
+    pub result: Option<Player>,
+}
+
+impl GameRecord {
+    /// An unfinished game on the full board.
+    pub fn new(moves: Vec<Move>) -> Self {
+        Self {
+            board_rows: ROWS,
+            board_cols: COLS,
+            moves,
+            result: None,
+        }
+    }
+
+    /// The opening board, or `None` if the dimensions don't fit in 5×8.
+    pub fn start(&self) -> Option<BoardState> {
+        BoardState::corner(self.board_rows, self.board_cols)
+    }
+
+    /// Every board from the opening through the last move.
+    ///
+    /// Replay stops early at the first move that is off the board or lands on
+    /// an eaten cell, so a corrupt record yields fewer than `moves.len() + 1`
+    /// boards; an unusable board size yields none.
+    pub fn replay(&self) -> Vec<BoardState> {
+        let Some(mut state) = self.start() else {
+            return Vec::new();
+        };
+        let row_offset = (ROWS - self.board_rows) as u8;
+        let col_offset = (COLS - self.board_cols) as u8;
+        let mut boards = vec![state];
+        for &mv in &self.moves {
+            if mv.row as usize >= self.board_rows || mv.col as usize >= self.board_cols {
+                break;
+            }
+            let mv = Move::new(mv.row + row_offset, mv.col + col_offset);
+            if state.heights()[mv.col as usize] >= mv.row as i8 {
+                break;
+            }
+            state = state.apply_move(mv);
+            boards.push(state);
+        }
+        boards
+    }
+
+    /// The game as PGN-style text: tag pairs for the board size and result,
+    /// a blank line, then numbered move pairs in algebraic notation ending
+    /// with the result (`1-0`, `0-1`, or `*` while unfinished).
+    pub fn to_pgn(&self) -> String {
+        let result = match self.result {
+            Some(Player::One) => "1-0",
+            Some(Player::Two) => "0-1",
+            None => "*",
+        };
+        let mut movetext = Vec::new();
+        for (turn, pair) in self.moves.chunks(2).enumerate() {
+            movetext.push(format!("{}.", turn + 1));
+            movetext.extend(pair.iter().map(|&mv| to_algebraic(mv)));
+        }
+        movetext.push(result.to_string());
+        format!(
+            "[Event \"Chomping Glass\"]\n[Board \"{}x{}\"]\n[Result \"{}\"]\n\n{}\n",
+            self.board_rows,
+            self.board_cols,
+            result,
+            movetext.join(" ")
+        )
+    }
+}
+
+/// Write `games` to `path` as PGN-style text (see [`GameRecord::to_pgn`]),
+/// separated by blank lines.
+pub fn export_games_pgn<P: AsRef<Path>>(games: &[GameRecord], path: P) -> anyhow::Result<()> {
+    let text: Vec<String> = games.iter().map(GameRecord::to_pgn).collect();
+    std::fs::write(path, text.join("\n"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finished_game() -> GameRecord {
+        GameRecord {
+            result: Some(Player::One),
+            ..GameRecord::new(vec![
+                Move::new(0, 1),
+                Move::new(1, 0),
+                Move::new(0, 4),
+                Move::new(4, 0),
+                Move::new(3, 5),
+                Move::new(4, 6),
+                Move::new(3, 7),
+            ])
+        }
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let record = finished_game();
+        let json = serde_json::to_string(&record).unwrap();
+        let back: GameRecord = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, record);
+    }
+
+    #[test]
+    fn two_move_game_exports_as_pgn() {
+        let game = GameRecord {
+            result: Some(Player::Two),
+            ..GameRecord::new(vec![Move::new(0, 1), Move::new(1, 0)])
+        };
+        let path = std::env::temp_dir().join("chomp_pgn_export_test.pgn");
+        export_games_pgn(&[game.clone(), game], &path).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let first = text.split("\n\n[").next().unwrap();
+        let (tags, movetext) = first.split_once("\n\n").unwrap();
+        assert!(tags
+            .lines()
+            .all(|tag| tag.starts_with('[') && tag.ends_with(']')));
+        assert!(tags.contains("[Result \"0-1\"]"));
+        let tokens: Vec<&str> = movetext.split_whitespace().collect();
+        assert_eq!(tokens, ["1.", "b1", "a2", "0-1"]);
+        assert_eq!(
+            crate::notation::parse_algebraic(tokens[1]),
+            Some(Move::new(0, 1))
+        );
+        assert_eq!(text.matches("[Event").count(), 2);
+    }
+
+    #[test]
+    fn replay_ends_on_a_terminal_board() {
+        let record = finished_game();
+        let boards = record.replay();
+        assert_eq!(boards.len(), record.moves.len() + 1);
+        assert_eq!(boards[0], BoardState::new());
+        assert!(record.result.is_some());
+        assert!(boards.last().unwrap().is_terminal());
+        assert!(boards[..boards.len() - 1].iter().all(|b| !b.is_terminal()));
+    }
+
+    #[test]
+    fn smaller_boards_sit_in_the_poison_corner() {
+        let record = GameRecord {
+            board_rows: 2,
+            board_cols: 2,
+            moves: vec![Move::new(0, 1), Move::new(1, 0)],
+            result: Some(Player::Two),
+        };
+        let boards = record.replay();
+        assert_eq!(boards[0].remaining_candies(), 3);
+        assert_eq!(boards[0].legal_moves().len(), 3);
+        assert_eq!(
+            boards[2],
+            BoardState::from_heights([4, 4, 4, 4, 4, 4, 4, 3])
+        );
+        assert!(boards[2].is_terminal());
+    }
+
+    #[test]
+    fn replay_stops_at_bad_moves() {
+        let record = GameRecord::new(vec![Move::new(1, 1), Move::new(0, 0), Move::new(3, 3)]);
+        assert_eq!(record.replay().len(), 2);
+        let off_board = GameRecord {
+            board_rows: 2,
+            board_cols: 2,
+            ..GameRecord::new(vec![Move::new(2, 0)])
+        };
+        assert_eq!(off_board.replay().len(), 1);
+        let too_big = GameRecord {
+            board_cols: COLS + 1,
+            ..GameRecord::new(Vec::new())
+        };
+        assert!(too_big.replay().is_empty());
+    }
+}
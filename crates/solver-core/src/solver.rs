@@ -0,0 +1,3974 @@
+//! Solving, analysis and policy export on top of the [`crate::board`] types.
+
+#[cfg(feature = "parallel")]
+use crate::parallel;
+use crate::{notation, opcode, BoardState, Move, Player, COLS, POISON, ROWS};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::path::Path;
+use std::rc::Rc;
+use std::str::FromStr;
+use thiserror::Error;
+
+impl Move {
+    pub fn to_one_indexed(self) -> (u8, u8) {
+        DisplayMove::from(self).to_tuple()
+    }
+
+    /// Reflection across the diagonal through the poison, or `None` if it
+    /// lands off the board.
+    pub fn mirror(self) -> Option<Move> {
+        let up = POISON.row.checked_sub(self.row)?;
+        let left = POISON.col.checked_sub(self.col)?;
+        Some(Move::new(
+            POISON.row.checked_sub(left)?,
+            POISON.col.checked_sub(up)?,
+        ))
+    }
+}
+
+/// A move in the one-indexed coordinates shown to players and packed into
+/// opcodes: `(1,2)` is `Move { row: 0, col: 1 }`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct DisplayMove {
+    pub row: u8,
+    pub col: u8,
+}
+
+impl DisplayMove {
+    pub const fn new(row: u8, col: u8) -> Self {
+        Self { row, col }
+    }
+
+    pub const fn to_tuple(self) -> (u8, u8) {
+        (self.row, self.col)
+    }
+}
+
+impl fmt::Display for DisplayMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({},{})", self.row, self.col)
+    }
+}
+
+impl From<Move> for DisplayMove {
+    fn from(mv: Move) -> Self {
+        Self::new(mv.row + 1, mv.col + 1)
+    }
+}
+
+/// A one-indexed coordinate that falls off the board.
+#[derive(Debug, Error, Clone, Copy, Eq, PartialEq)]
+pub enum MoveRangeError {
+    #[error("row must be between 1 and {max}")]
+    Row { max: usize },
+    #[error("column must be between 1 and {max}")]
+    Col { max: usize },
+}
+
+impl TryFrom<DisplayMove> for Move {
+    type Error = MoveRangeError;
+
+    fn try_from(mv: DisplayMove) -> Result<Self, Self::Error> {
+        if !(1..=ROWS as u8).contains(&mv.row) {
+            return Err(MoveRangeError::Row { max: ROWS });
+        }
+        if !(1..=COLS as u8).contains(&mv.col) {
+            return Err(MoveRangeError::Col { max: COLS });
+        }
+        Ok(Move::new(mv.row - 1, mv.col - 1))
+    }
+}
+
+impl BoardState {
+    /// Build a board from a per-cell eaten mask (`cells[row][col]`). Returns
+    /// `None` unless the eaten cells form a staircase that spares the poison.
+    pub fn from_cells(cells: &[[bool; COLS]; ROWS]) -> Option<Self> {
+        let mut heights = [-1i8; COLS];
+        for (col, height) in heights.iter_mut().enumerate() {
+            let eaten = (0..ROWS).take_while(|&row| cells[row][col]).count();
+            if (eaten..ROWS).any(|row| cells[row][col]) {
+                return None;
+            }
+            *height = eaten as i8 - 1;
+        }
+        let state = Self { heights };
+        state.is_reachable().then_some(state)
+    }
+
+    /// Parse the grid printed by `Display`: one line per row, `o` for a candy,
+    /// `.` for an eaten cell and `X` for the poison, whitespace ignored.
+    pub fn from_diagram(diagram: &str) -> Option<Self> {
+        let rows: Vec<Vec<char>> = diagram
+            .lines()
+            .map(|line| line.chars().filter(|c| !c.is_whitespace()).collect())
+            .filter(|row: &Vec<char>| !row.is_empty())
+            .collect();
+        if rows.len() != ROWS || rows.iter().any(|row| row.len() != COLS) {
+            return None;
+        }
+        let mut cells = [[false; COLS]; ROWS];
+        for (r, row) in rows.iter().enumerate() {
+            for (c, &symbol) in row.iter().enumerate() {
+                let is_poison = (r as u8, c as u8) == POISON.to_tuple();
+                cells[r][c] = match symbol {
+                    '.' if !is_poison => true,
+                    'o' if !is_poison => false,
+                    'X' if is_poison => false,
+                    _ => return None,
+                };
+            }
+        }
+        Self::from_cells(&cells)
+    }
+
+    /// Compact text form: one digit per column giving how many candies have
+    /// been eaten from it, e.g. `"11000000"` after the opening move.
+    pub fn to_compact(&self) -> String {
+        self.heights
+            .iter()
+            .map(|&h| char::from(b'0' + (h + 1) as u8))
+            .collect()
+    }
+
+    /// Parse [`BoardState::to_compact`] output, accepting only reachable boards.
+    pub fn from_compact(raw: &str) -> Option<Self> {
+        let digits: Vec<u32> = raw
+            .trim()
+            .chars()
+            .map(|c| c.to_digit(10))
+            .collect::<Option<_>>()?;
+        if digits.len() != COLS || digits.iter().any(|&d| d as usize > ROWS) {
+            return None;
+        }
+        let mut heights = [-1i8; COLS];
+        for (height, &d) in heights.iter_mut().zip(&digits) {
+            *height = d as i8 - 1;
+        }
+        let state = Self { heights };
+        state.is_reachable().then_some(state)
+    }
+
+    /// Pack the heights into an integer: 3 bits per column holding the
+    /// eaten count (as in [`BoardState::to_compact`]), column 0 lowest.
+    pub fn to_u64(&self) -> u64 {
+        self.heights
+            .iter()
+            .enumerate()
+            .map(|(col, &h)| ((h + 1) as u64) << (3 * col))
+            .sum()
+    }
+
+    /// Unpack [`BoardState::to_u64`], accepting only reachable boards.
+    pub fn from_u64(packed: u64) -> Option<Self> {
+        if packed >> (3 * COLS) != 0 {
+            return None;
+        }
+        let mut heights = [-1i8; COLS];
+        for (col, height) in heights.iter_mut().enumerate() {
+            let eaten = (packed >> (3 * col)) & 0b111;
+            if eaten as usize > ROWS {
+                return None;
+            }
+            *height = eaten as i8 - 1;
+        }
+        let state = Self { heights };
+        state.is_reachable().then_some(state)
+    }
+
+    /// Write the board to `path` in the compact format.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> anyhow::Result<()> {
+        std::fs::write(path, format!("{}\n", self.to_compact()))?;
+        Ok(())
+    }
+
+    /// Read a board written by [`BoardState::save`], rejecting illegal shapes.
+    pub fn load<P: AsRef<Path>>(path: P) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let raw = std::fs::read_to_string(path)?;
+        Self::from_compact(&raw).ok_or_else(|| {
+            anyhow::anyhow!(
+                "{} does not hold a legal board in compact form",
+                path.display()
+            )
+        })
+    }
+
+    /// Young-diagram partition of the eaten cells: one part per touched column,
+    /// holding the number of candies eaten from it (non-increasing, no zeros).
+    pub fn to_partition(&self) -> Vec<usize> {
+        self.heights
+            .iter()
+            .map(|&h| (h + 1) as usize)
+            .take_while(|&part| part > 0)
+            .collect()
+    }
+
+    /// Inverse of [`BoardState::to_partition`]. Returns `None` if the parts are
+    /// not non-increasing, do not fit on the board, or would eat the poison.
+    pub fn from_partition(parts: &[usize]) -> Option<Self> {
+        if parts.len() > COLS || parts.windows(2).any(|w| w[1] > w[0]) {
+            return None;
+        }
+        let mut heights = [-1i8; COLS];
+        for (col, &part) in parts.iter().enumerate() {
+            if part == 0 || part > ROWS {
+                return None;
+            }
+            if col == POISON.col as usize && part > POISON.row as usize {
+                return None;
+            }
+            heights[col] = part as i8 - 1;
+        }
+        Some(Self { heights })
+    }
+
+    /// [`BoardState::apply_move`] for a variant that eats toward `direction`;
+    /// `mv` is in that variant's board coordinates.
+    pub fn apply_move_toward(&self, mv: Move, direction: EatDirection) -> Self {
+        self.apply_move(direction.to_standard(mv))
+    }
+
+    /// [`BoardState::legal_moves`] in the board coordinates of `direction`,
+    /// leaving out [`EatDirection::poison`].
+    pub fn legal_moves_toward(&self, direction: EatDirection) -> Vec<Move> {
+        self.legal_moves()
+            .into_iter()
+            .map(|mv| direction.to_standard(mv))
+            .collect()
+    }
+
+    /// [`BoardState::legal_moves`] for a variant with several poison squares:
+    /// any move whose bite would take an uneaten poison is excluded.
+    pub fn legal_moves_with_poisons(&self, poisons: &HashSet<Move>) -> Vec<Move> {
+        let live: Vec<Move> = poisons
+            .iter()
+            .copied()
+            .filter(|p| p.row as i8 > self.heights[p.col as usize])
+            .collect();
+        self.uneaten_cells()
+            .filter(|mv| !live.iter().any(|p| mv.row >= p.row && mv.col >= p.col))
+            .collect()
+    }
+
+    /// [`BoardState::is_terminal`] with several poison squares.
+    pub fn is_terminal_with_poisons(&self, poisons: &HashSet<Move>) -> bool {
+        self.legal_moves_with_poisons(poisons).is_empty()
+    }
+
+    /// The [`fmt::Display`] diagram with every square in `poisons` drawn as `X`.
+    pub fn render_with_poisons(&self, poisons: &HashSet<Move>) -> String {
+        let mut out = String::new();
+        self.write_diagram(&mut out, |mv| poisons.contains(&mv))
+            .expect("writing to a String cannot fail");
+        out
+    }
+
+    fn write_diagram<W: fmt::Write>(
+        &self,
+        out: &mut W,
+        is_poison: impl Fn(Move) -> bool,
+    ) -> fmt::Result {
+        for row in 0..ROWS {
+            for col in 0..COLS {
+                let eaten = self.heights[col] >= row as i8;
+                let symbol = if is_poison(Move::new(row as u8, col as u8)) {
+                    'X'
+                } else if eaten {
+                    '.'
+                } else {
+                    'o'
+                };
+                write!(out, "  {}", symbol)?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    /// Uneaten candies directly above or to the left of the poison. The poison
+    /// sits in the corner, so it has at most two neighbors; 0 means the next
+    /// player must take it. Also 0 if the poison itself is gone.
+    pub fn poison_exposure(&self) -> u8 {
+        let (row, col) = (POISON.row as i8, POISON.col as usize);
+        if self.heights[col] >= row {
+            return 0;
+        }
+        let above = u8::from(self.heights[col] < row - 1);
+        let left = u8::from(col > 0 && self.heights[col - 1] < row);
+        above + left
+    }
+
+    /// Candies still on the board, not counting the poison.
+    pub fn remaining_candies(&self) -> usize {
+        let uneaten: usize = self
+            .heights
+            .iter()
+            .map(|&h| (ROWS as i8 - 1 - h) as usize)
+            .sum();
+        uneaten - usize::from(self.heights[POISON.col as usize] < POISON.row as i8)
+    }
+
+    /// Material balance for the candy-scoring variant: candies eaten by
+    /// [`Player::One`] minus those eaten by [`Player::Two`], with
+    /// `player_eaten_counts` indexed the same way. The poison never scores.
+    ///
+    /// Panics in debug builds if the counts don't add up to the candies eaten
+    /// since the full board.
+    pub fn score(&self, player_eaten_counts: [usize; 2]) -> i32 {
+        let [one, two] = player_eaten_counts;
+        debug_assert_eq!(
+            one + two,
+            ROWS * COLS - 1 - self.remaining_candies(),
+            "eaten counts don't match the board"
+        );
+        one as i32 - two as i32
+    }
+
+    /// Rough stage of the game by the share of candies left, using
+    /// [`OPENING_PHASE_PERCENT`] and [`ENDGAME_PHASE_PERCENT`].
+    pub fn phase(&self) -> GamePhase {
+        let left = self.remaining_candies() * 100;
+        let total = ROWS * COLS - 1;
+        if left > OPENING_PHASE_PERCENT * total {
+            GamePhase::Opening
+        } else if left < ENDGAME_PHASE_PERCENT * total {
+            GamePhase::Endgame
+        } else {
+            GamePhase::Midgame
+        }
+    }
+
+    /// Minimum number of legal moves (ignoring strategy) needed to eat every
+    /// candy in `col`. A single bite at the column's bottom cell clears it, so
+    /// this is 0 or 1; the poison column only counts the candies above the poison.
+    ///
+    /// Panics if `col >= COLS`.
+    pub fn moves_to_clear_column(&self, col: usize) -> usize {
+        let bottom = if col == POISON.col as usize {
+            POISON.row as i8 - 1
+        } else {
+            ROWS as i8 - 1
+        };
+        usize::from(self.heights[col] < bottom)
+    }
+
+    /// Number of plies played since the empty board, assuming every move left a
+    /// visible corner in the eaten staircase.
+    ///
+    /// The heights alone cannot tell how many bites made a shape (a later bite
+    /// can swallow an earlier one), so this is the *minimum* ply count: one per
+    /// outer corner. Callers that need the exact count must track it themselves.
+    pub fn ply_count(&self) -> usize {
+        (0..COLS)
+            .filter(|&col| {
+                let h = self.heights[col];
+                h >= 0 && (col + 1 == COLS || self.heights[col + 1] < h)
+            })
+            .count()
+    }
+
+    /// Side to move under the [`BoardState::ply_count`] assumption, with
+    /// [`Player::One`] opening.
+    pub fn side_to_move(&self) -> Player {
+        match self.ply_count() % 2 {
+            0 => Player::One,
+            _ => Player::Two,
+        }
+    }
+
+    /// Moves that leave only the poison, forcing the opponent to take it next.
+    pub fn winning_finishers(&self) -> Vec<Move> {
+        self.legal_moves()
+            .into_iter()
+            .filter(|&mv| self.apply_move(mv).is_terminal())
+            .collect()
+    }
+
+    /// Every uneaten candy except the poison, in `legal_moves` order, with
+    /// whether biting at it keeps the mover winning. Each candy is its own
+    /// move, so this covers the whole grid rather than one cell per column.
+    pub fn candy_criticality(&self, solver: &mut Solver) -> Vec<(Move, bool)> {
+        self.legal_moves()
+            .into_iter()
+            .map(|mv| (mv, !solver.is_winning(self.apply_move(mv))))
+            .collect()
+    }
+
+    /// A fresh `rows × cols` board in the poison corner of the full grid, the
+    /// usual stand-in for smaller boards. `None` if it doesn't fit in 5×8.
+    pub fn corner(rows: usize, cols: usize) -> Option<BoardState> {
+        if !(1..=ROWS).contains(&rows) || !(1..=COLS).contains(&cols) {
+            return None;
+        }
+        let mut heights = [(ROWS - rows) as i8 - 1; COLS];
+        for height in heights.iter_mut().take(COLS - cols) {
+            *height = ROWS as i8 - 1;
+        }
+        Some(BoardState { heights })
+    }
+
+    /// How the board relates to its reflection across the diagonal through
+    /// the poison. Only boards whose remaining candies fit a square in the
+    /// poison corner (as tall as they are wide) can be reflected; anything
+    /// else is [`SymmetryClass::Asymmetric`].
+    pub fn symmetry_class(&self) -> SymmetryClass {
+        let tall = (ROWS as i8 - 1 - self.heights[COLS - 1]) as usize;
+        let wide = COLS
+            - self
+                .heights
+                .iter()
+                .position(|&h| h < ROWS as i8 - 1)
+                .unwrap_or(COLS);
+        if tall != wide {
+            return SymmetryClass::Asymmetric;
+        }
+        let mut heights = [-1i8; COLS];
+        for (col, height) in heights.iter_mut().enumerate() {
+            for row in 0..ROWS {
+                let uneaten = Move::new(row as u8, col as u8)
+                    .mirror()
+                    .is_some_and(|mv| mv.row as i8 > self.heights[mv.col as usize]);
+                if !uneaten {
+                    *height = row as i8;
+                }
+            }
+        }
+        let partner = BoardState { heights };
+        if partner == *self {
+            SymmetryClass::Symmetric
+        } else {
+            SymmetryClass::Partner(partner)
+        }
+    }
+}
+
+/// Result of [`BoardState::symmetry_class`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum SymmetryClass {
+    /// The board is its own reflection.
+    Symmetric,
+    /// The reflection is this other board, which has the same verdict.
+    Partner(BoardState),
+    /// The remaining candies do not fit a square, so there is no reflection.
+    Asymmetric,
+}
+
+impl fmt::Display for BoardState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_diagram(f, |mv| mv == POISON)
+    }
+}
+
+/// Verdict for a single legal move from a position.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MoveAnalysis {
+    pub mv: Move,
+    /// Whether the move leaves the opponent in a losing position.
+    pub winning: bool,
+    /// For a winning move, whether any other move also wins; `None` otherwise.
+    pub margin: Option<WinMargin>,
+}
+
+/// How much room for error a winning move leaves.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum WinMargin {
+    /// The only winning move from its position.
+    Unique,
+    /// One of several winning moves.
+    OneOfSeveral,
+}
+
+/// Boards with more than this percentage of their candies left are in the
+/// [`GamePhase::Opening`].
+pub const OPENING_PHASE_PERCENT: usize = 75;
+/// Boards with less than this percentage of their candies left are in the
+/// [`GamePhase::Endgame`].
+pub const ENDGAME_PHASE_PERCENT: usize = 25;
+
+/// Coarse stage of a game, for framing tips in a front-end.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum GamePhase {
+    Opening,
+    Midgame,
+    Endgame,
+}
+
+/// How to choose one move when several are equally good for the outcome.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Tiebreak {
+    /// The first candidate in `legal_moves` order.
+    #[default]
+    First,
+    /// The move that finishes the game in the fewest plies.
+    Fastest,
+    /// The move that eats the most candies.
+    MostEaten,
+}
+
+impl FromStr for Tiebreak {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        match raw {
+            "first" => Ok(Tiebreak::First),
+            "fastest" => Ok(Tiebreak::Fastest),
+            "most-eaten" => Ok(Tiebreak::MostEaten),
+            other => Err(format!(
+                "unknown tiebreak {:?} (expected first, fastest or most-eaten)",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Tiebreak {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Tiebreak::First => "first",
+            Tiebreak::Fastest => "fastest",
+            Tiebreak::MostEaten => "most-eaten",
+        })
+    }
+}
+
+/// Bitmask (bit `row * COLS + col`) of the cells eaten going from `before` to `after`.
+fn eaten_mask(before: BoardState, after: BoardState) -> u64 {
+    let mut mask = 0;
+    for (col, (&from, &to)) in before.heights.iter().zip(after.heights.iter()).enumerate() {
+        for row in (from + 1)..=to {
+            mask |= 1 << (row as usize * COLS + col);
+        }
+    }
+    mask
+}
+
+/// Why the moves from a position lose: each paired with the opponent's
+/// winning answer. From a lost position this covers every legal move.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LossExplanation {
+    /// `(our move, opponent's winning reply)` in `legal_moves` order.
+    pub refutations: Vec<(Move, Move)>,
+}
+
+/// A ply where the mover held a win and played a losing move, from
+/// [`Solver::analyze_game`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Blunder {
+    /// Zero-based index of the move in the game.
+    pub ply: usize,
+    pub mover: Player,
+    pub played: Move,
+    /// The first winning move that was available instead.
+    pub winning_move: Move,
+}
+
+/// Proof that the side to move wins: one winning move, then a certificate
+/// answering every reply the opponent can make to it.
+///
+/// Subtrees for positions reached along several lines are shared, so the
+/// proof stays small even though it reads as a tree.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WinCertificate {
+    /// The board the certificate starts from.
+    pub state: BoardState,
+    /// A move into a losing position for the opponent.
+    pub mv: Move,
+    /// Every opponent reply after `mv`, each with how we go on winning.
+    /// Empty when `mv` leaves only the poison.
+    pub replies: Vec<(Move, Rc<WinCertificate>)>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Evaluation {
+    pub winning: bool,
+    pub winning_moves: Vec<Move>,
+}
+
+/// An [`Evaluation`] whose moves serialize as algebraic strings (`"b1"`)
+/// instead of zero-indexed `{row, col}` objects, for tables people read.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(into = "AlgebraicRepr", try_from = "AlgebraicRepr")]
+pub struct AlgebraicEvaluation(pub Evaluation);
+
+/// Serialized form of [`AlgebraicEvaluation`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+struct AlgebraicRepr {
+    winning: bool,
+    winning_moves: Vec<String>,
+}
+
+impl From<AlgebraicEvaluation> for AlgebraicRepr {
+    fn from(AlgebraicEvaluation(eval): AlgebraicEvaluation) -> Self {
+        Self {
+            winning: eval.winning,
+            winning_moves: eval
+                .winning_moves
+                .into_iter()
+                .map(notation::to_algebraic)
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<AlgebraicRepr> for AlgebraicEvaluation {
+    type Error = String;
+
+    fn try_from(repr: AlgebraicRepr) -> Result<Self, Self::Error> {
+        let winning_moves = repr
+            .winning_moves
+            .iter()
+            .map(|raw| {
+                notation::parse_algebraic(raw).ok_or_else(|| format!("invalid move {:?}", raw))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self(Evaluation {
+            winning: repr.winning,
+            winning_moves,
+        }))
+    }
+}
+
+/// Which way a move eats from the chosen candy. Every variant is a mirror
+/// image of standard Chomp, so a [`BoardState`] always stores the standard
+/// (up-and-left) frame and variant moves are reflected into it; verdicts
+/// from [`Solver`] carry over unchanged.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum EatDirection {
+    /// Rows above and columns to the left; the poison is bottom-right.
+    #[default]
+    UpLeft,
+    /// Rows above and columns to the right; the poison is bottom-left.
+    UpRight,
+    /// Rows below and columns to the left; the poison is top-right.
+    DownLeft,
+    /// Rows below and columns to the right; the poison is top-left.
+    DownRight,
+}
+
+impl EatDirection {
+    /// Reflect a move between this variant's coordinates and the standard
+    /// frame. Reflections are their own inverse, so this maps both ways.
+    pub fn to_standard(self, mv: Move) -> Move {
+        let flip_row = |row: u8| (ROWS - 1) as u8 - row;
+        let flip_col = |col: u8| (COLS - 1) as u8 - col;
+        match self {
+            EatDirection::UpLeft => mv,
+            EatDirection::UpRight => Move::new(mv.row, flip_col(mv.col)),
+            EatDirection::DownLeft => Move::new(flip_row(mv.row), mv.col),
+            EatDirection::DownRight => Move::new(flip_row(mv.row), flip_col(mv.col)),
+        }
+    }
+
+    /// Where the poison sits in this variant's coordinates.
+    pub fn poison(self) -> Move {
+        self.to_standard(POISON)
+    }
+}
+
+/// Outcome for the player to move, without the move list of an [`Evaluation`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Verdict {
+    Win,
+    Loss,
+}
+
+impl Verdict {
+    pub fn from_winning(winning: bool) -> Self {
+        if winning {
+            Verdict::Win
+        } else {
+            Verdict::Loss
+        }
+    }
+}
+
+/// Which rule decides the loser.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum GameMode {
+    /// Standard Chomping Glass: whoever is left with only the poison loses.
+    #[default]
+    Misere,
+    /// The poison is an ordinary candy and whoever cannot move loses.
+    Normal,
+}
+
+/// Work counters for a [`Solver`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SolverStats {
+    /// Positions solved from scratch (cache misses).
+    pub nodes_expanded: u64,
+    /// Lookups answered from the cache.
+    pub cache_hits: u64,
+}
+
+/// Memoizing solver for the 5×8 board.
+#[derive(Default)]
+pub struct Solver {
+    mode: GameMode,
+    cache: HashMap<BoardState, Evaluation>,
+    depth_cache: HashMap<BoardState, u32>,
+    verdict_cache: HashMap<BoardState, Verdict>,
+    stats: SolverStats,
+    node_budget: Option<u64>,
+    candy_cache: HashMap<BoardState, i32>,
+}
+
+/// Result of [`Solver::evaluate_bounded`].
+#[derive(Clone, Debug)]
+pub struct BoundedEvaluation {
+    /// Exact unless `budget_limited`. When the budget ran out it lists only
+    /// the winning moves already proven, and claims a win only if there is one.
+    pub evaluation: Evaluation,
+    /// Whether the node budget ran out before the position was solved.
+    pub budget_limited: bool,
+}
+
+impl Solver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Solver for the given rule set; [`Solver::new`] uses [`GameMode::Misere`].
+    pub fn with_mode(mode: GameMode) -> Self {
+        Self {
+            mode,
+            ..Self::default()
+        }
+    }
+
+    /// Misère solver whose cache starts out holding the policy table solved
+    /// at build time, so evaluating any reachable board is a lookup.
+    #[cfg(feature = "embed-policy")]
+    pub fn embedded() -> Self {
+        Self {
+            cache: crate::embedded::entries().collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Misère solver whose [`Solver::evaluate_bounded`] expands at most
+    /// `max_nodes` new positions per call.
+    ///
+    /// The budget deliberately applies to `evaluate_bounded` alone:
+    /// [`Solver::evaluate`] and everything built on it stay exact and
+    /// unbounded, since their callers rely on a complete answer.
+    pub fn with_node_budget(max_nodes: u64) -> Self {
+        Self {
+            node_budget: Some(max_nodes),
+            ..Self::default()
+        }
+    }
+
+    pub fn mode(&self) -> GameMode {
+        self.mode
+    }
+
+    /// Moves available to the mover under this solver's rules.
+    fn moves(&self, state: BoardState) -> Vec<Move> {
+        let mut moves = state.legal_moves();
+        if self.mode == GameMode::Normal && state.heights[POISON.col as usize] < POISON.row as i8 {
+            moves.push(POISON);
+        }
+        moves
+    }
+
+    pub fn evaluate(&mut self, state: BoardState) -> Evaluation {
+        self.evaluate_inner(state, &mut || false)
+            .expect("evaluation without a cancel check always completes")
+    }
+
+    /// Like [`Solver::evaluate`], but polls `cancel` before expanding each new
+    /// position and returns `None` as soon as it reports `true`, e.g.
+    /// `|| flag.load(Ordering::Relaxed)`.
+    ///
+    /// Positions are cached only once their whole subtree is solved, so an
+    /// interrupted run leaves no partial entries behind and a later call
+    /// resumes from whatever was finished.
+    pub fn evaluate_with_cancel<C: FnMut() -> bool>(
+        &mut self,
+        state: BoardState,
+        mut cancel: C,
+    ) -> Option<Evaluation> {
+        self.evaluate_inner(state, &mut cancel)
+    }
+
+    /// Like [`Solver::evaluate`], but stops expanding once this solver's node
+    /// budget (see [`Solver::with_node_budget`]) is spent and falls back to
+    /// what the cache already proves about the successors. Unbounded without
+    /// a budget. As with cancellation, only fully solved positions are cached.
+    pub fn evaluate_bounded(&mut self, state: BoardState) -> BoundedEvaluation {
+        let mut left = self.node_budget.unwrap_or(u64::MAX);
+        let mut spent = || match left.checked_sub(1) {
+            Some(rest) => {
+                left = rest;
+                false
+            }
+            None => true,
+        };
+        if let Some(evaluation) = self.evaluate_inner(state, &mut spent) {
+            return BoundedEvaluation {
+                evaluation,
+                budget_limited: false,
+            };
+        }
+        let winning_moves: Vec<Move> = self
+            .moves(state)
+            .into_iter()
+            .filter(|&mv| {
+                let next = state.apply_move(mv);
+                self.cache.get(&next).is_some_and(|eval| !eval.winning)
+                    || self.verdict_cache.get(&next) == Some(&Verdict::Loss)
+            })
+            .collect();
+        BoundedEvaluation {
+            evaluation: Evaluation {
+                winning: !winning_moves.is_empty(),
+                winning_moves,
+            },
+            budget_limited: true,
+        }
+    }
+
+    /// Counters accumulated since this solver was created.
+    pub fn stats(&self) -> SolverStats {
+        self.stats
+    }
+
+    /// Check that the cached evaluations agree with each other: winning
+    /// positions have a winning move, losing ones have none, and every
+    /// winning move leads to a successor that is cached as lost. Returns a
+    /// description of the first inconsistency found.
+    pub fn verify_invariants(&self) -> Result<(), String> {
+        for (state, eval) in &self.cache {
+            if eval.winning == eval.winning_moves.is_empty() {
+                return Err(format!(
+                    "{:?} is marked {} but has {} winning moves",
+                    state.heights,
+                    if eval.winning { "winning" } else { "losing" },
+                    eval.winning_moves.len()
+                ));
+            }
+            for &mv in &eval.winning_moves {
+                let next = state.apply_move(mv);
+                match self.cache.get(&next) {
+                    Some(successor) if !successor.winning => {}
+                    Some(_) => {
+                        return Err(format!(
+                            "winning move {:?} from {:?} leads to a winning position",
+                            mv.to_tuple(),
+                            state.heights
+                        ))
+                    }
+                    None => {
+                        return Err(format!(
+                            "winning move {:?} from {:?} leads to an uncached position",
+                            mv.to_tuple(),
+                            state.heights
+                        ))
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn evaluate_inner(
+        &mut self,
+        state: BoardState,
+        cancel: &mut dyn FnMut() -> bool,
+    ) -> Option<Evaluation> {
+        if let Some(entry) = self.cache.get(&state) {
+            self.stats.cache_hits += 1;
+            return Some(entry.clone());
+        }
+        if cancel() {
+            return None;
+        }
+        self.stats.nodes_expanded += 1;
+
+        let moves = self.moves(state);
+        if moves.is_empty() {
+            let eval = Evaluation {
+                winning: false,
+                winning_moves: Vec::new(),
+            };
+            self.cache.insert(state, eval.clone());
+            return Some(eval);
+        }
+
+        let mut winning_moves = Vec::new();
+        for mv in moves {
+            let next_state = state.apply_move(mv);
+            if !self.evaluate_inner(next_state, cancel)?.winning {
+                winning_moves.push(mv);
+            }
+        }
+
+        let eval = Evaluation {
+            winning: !winning_moves.is_empty(),
+            winning_moves,
+        };
+        self.cache.insert(state, eval.clone());
+        Some(eval)
+    }
+
+    /// Whether the player to move wins, without collecting every winning move.
+    ///
+    /// Stops at the first move into a losing position, so it expands no more
+    /// nodes than [`Solver::evaluate`] on the same cache.
+    pub fn is_winning(&mut self, state: BoardState) -> bool {
+        self.verdict(state) == Verdict::Win
+    }
+
+    /// [`Solver::is_winning`] as a [`Verdict`]. Walks moves without building
+    /// move lists, so a verdict-only solve allocates little beyond its cache.
+    pub fn verdict(&mut self, state: BoardState) -> Verdict {
+        if let Some(entry) = self.cache.get(&state) {
+            self.stats.cache_hits += 1;
+            return Verdict::from_winning(entry.winning);
+        }
+        if let Some(&verdict) = self.verdict_cache.get(&state) {
+            self.stats.cache_hits += 1;
+            return verdict;
+        }
+        self.stats.nodes_expanded += 1;
+
+        let normal = self.mode == GameMode::Normal;
+        let winning = state
+            .uneaten_cells()
+            .filter(|&mv| normal || mv != POISON)
+            .any(|mv| self.verdict(state.apply_move(mv)) == Verdict::Loss);
+        let verdict = Verdict::from_winning(winning);
+        self.verdict_cache.insert(state, verdict);
+        verdict
+    }
+
+    /// Classify every legal move from `state`, in `legal_moves` order.
+    pub fn analyze_moves(&mut self, state: BoardState) -> Vec<MoveAnalysis> {
+        let eval = self.evaluate(state);
+        let margin = match eval.winning_moves.len() {
+            1 => WinMargin::Unique,
+            _ => WinMargin::OneOfSeveral,
+        };
+        self.moves(state)
+            .into_iter()
+            .map(|mv| {
+                let winning = eval.winning_moves.contains(&mv);
+                MoveAnalysis {
+                    mv,
+                    winning,
+                    margin: winning.then_some(margin),
+                }
+            })
+            .collect()
+    }
+
+    /// Every opponent reply after `my_move` from `state`, with its verdict for
+    /// the opponent: [`Verdict::Win`] if the reply leaves us lost. If any reply
+    /// wins, `my_move` was not a winning move.
+    pub fn opponent_replies(&mut self, state: BoardState, my_move: Move) -> Vec<(Move, Verdict)> {
+        let after = state.apply_move(my_move);
+        self.moves(after)
+            .into_iter()
+            .map(|reply| {
+                let ours = self.verdict(after.apply_move(reply));
+                (reply, Verdict::from_winning(ours == Verdict::Loss))
+            })
+            .collect()
+    }
+
+    /// For each opponent reply to `mv` from `state`, in `legal_moves` order,
+    /// how many winning answers we then have. After a winning `mv` every
+    /// count is at least 1; replies with a count of 1 are the tight lines
+    /// where only one answer keeps the win.
+    pub fn continuation_tightness(&mut self, state: BoardState, mv: Move) -> Vec<(Move, usize)> {
+        let theirs = state.apply_move(mv);
+        self.moves(theirs)
+            .into_iter()
+            .map(|reply| {
+                let ours = self.evaluate(theirs.apply_move(reply));
+                (reply, ours.winning_moves.len())
+            })
+            .collect()
+    }
+
+    /// How many forced opponent replies follow `mv` from `state`. A reply is
+    /// forced when it is the opponent's only legal move or their only winning
+    /// one; after each forced reply the mover continues with
+    /// [`Solver::recommend`] (the first winning move) or, when lost, the
+    /// [`Solver::best_defense`], and the count stops at the first free choice
+    /// or once the game is over.
+    pub fn forcing_depth(&mut self, state: BoardState, mv: Move) -> usize {
+        let mut theirs = state.apply_move(mv);
+        let mut depth = 0;
+        loop {
+            let moves = theirs.legal_moves();
+            let winning = self.evaluate(theirs).winning_moves;
+            let forced = match (moves.as_slice(), winning.as_slice()) {
+                ([only], _) | (_, [only]) => *only,
+                _ => return depth,
+            };
+            depth += 1;
+            let ours = theirs.apply_move(forced);
+            let Some(next) = self
+                .recommend(ours, Tiebreak::First)
+                .or_else(|| self.best_defense(ours))
+            else {
+                return depth;
+            };
+            theirs = ours.apply_move(next);
+        }
+    }
+
+    /// Moves that another move from `state` dominates, in `legal_moves` order.
+    ///
+    /// Move A dominates move B when the position A leaves is strictly worse
+    /// for the opponent than the one B leaves. Successors are compared by
+    /// verdict first (lost for them beats won for them), then by
+    /// [`Solver::depth_to_win`]: a win that ends sooner, or a loss that
+    /// holds out longer. So slower winning moves are dominated too, and from
+    /// a losing position every defense shorter than the longest one is.
+    pub fn dominated_moves(&mut self, state: BoardState) -> Vec<Move> {
+        let moves = self.moves(state);
+        let outcomes: Vec<(bool, i64)> = moves
+            .iter()
+            .map(|&mv| {
+                let next = state.apply_move(mv);
+                let depth = i64::from(self.depth_to_win(next));
+                if self.is_winning(next) {
+                    (false, depth)
+                } else {
+                    (true, -depth)
+                }
+            })
+            .collect();
+        let Some(&best) = outcomes.iter().max() else {
+            return Vec::new();
+        };
+        moves
+            .into_iter()
+            .zip(outcomes)
+            .filter(|&(_, outcome)| outcome < best)
+            .map(|(mv, _)| mv)
+            .collect()
+    }
+
+    /// Replay `moves` from the full board, with [`Player::One`] moving first,
+    /// and list every ply that threw away a win. Moves from an already-lost
+    /// position are never blunders. Replay stops at the first illegal move.
+    pub fn analyze_game(&mut self, moves: &[Move]) -> Vec<Blunder> {
+        let mut state = BoardState::new();
+        let mut mover = Player::One;
+        let mut blunders = Vec::new();
+        for (ply, &played) in moves.iter().enumerate() {
+            if !state.legal_moves().contains(&played) {
+                break;
+            }
+            blunders.extend(self.blunder(state, ply, mover, played));
+            state = state.apply_move(played);
+            mover = mover.opponent();
+        }
+        blunders
+    }
+
+    /// The [`Blunder`] if `mover` held a win at `state` and `played` threw
+    /// it away. Shared by [`Solver::analyze_game`] and
+    /// [`crate::strategy::first_mistake`] so both judge moves the same way.
+    pub(crate) fn blunder(
+        &mut self,
+        state: BoardState,
+        ply: usize,
+        mover: Player,
+        played: Move,
+    ) -> Option<Blunder> {
+        let winning = self.evaluate(state).winning_moves;
+        let &winning_move = winning.first()?;
+        (!winning.contains(&played)).then_some(Blunder {
+            ply,
+            mover,
+            played,
+            winning_move,
+        })
+    }
+
+    /// For every losing move from `state`, name the opponent's reply that keeps
+    /// them winning. Winning moves have no such reply and are left out, so from
+    /// a winning position only the mistakes are listed.
+    pub fn explain_loss(&mut self, state: BoardState) -> LossExplanation {
+        let mut refutations = Vec::new();
+        for mv in self.moves(state) {
+            let reply = self
+                .evaluate(state.apply_move(mv))
+                .winning_moves
+                .first()
+                .copied();
+            if let Some(reply) = reply {
+                refutations.push((mv, reply));
+            }
+        }
+        LossExplanation { refutations }
+    }
+
+    /// Rough "temperature": the fraction of moves from `state` whose result
+    /// is classified differently (winning vs losing for its mover) than
+    /// `state` itself. 0.0 when no move is left.
+    pub fn urgency(&mut self, state: BoardState) -> f64 {
+        let winning = self.is_winning(state);
+        let moves = self.moves(state);
+        if moves.is_empty() {
+            return 0.0;
+        }
+        let changed = moves
+            .iter()
+            .filter(|&&mv| self.is_winning(state.apply_move(mv)) != winning)
+            .count();
+        changed as f64 / moves.len() as f64
+    }
+
+    /// A [`WinCertificate`] for `state`, or `None` if the side to move loses.
+    /// At each of our turns it uses the first winning move.
+    pub fn win_certificate(&mut self, state: BoardState) -> Option<WinCertificate> {
+        let mut memo = HashMap::new();
+        self.certify(state, &mut memo)
+            .map(|cert| Rc::try_unwrap(cert).unwrap_or_else(|shared| (*shared).clone()))
+    }
+
+    fn certify(
+        &mut self,
+        state: BoardState,
+        memo: &mut HashMap<BoardState, Rc<WinCertificate>>,
+    ) -> Option<Rc<WinCertificate>> {
+        if let Some(cert) = memo.get(&state) {
+            return Some(Rc::clone(cert));
+        }
+        let mv = *self.evaluate(state).winning_moves.first()?;
+        let next = state.apply_move(mv);
+        let mut replies = Vec::new();
+        for reply in self.moves(next) {
+            let cert = self
+                .certify(next.apply_move(reply), memo)
+                .expect("every reply from a lost position leaves a win");
+            replies.push((reply, cert));
+        }
+        let cert = Rc::new(WinCertificate { state, mv, replies });
+        memo.insert(state, Rc::clone(&cert));
+        Some(cert)
+    }
+
+    /// Plies until the game ends under optimal play: the winner finishes as
+    /// fast as possible and the loser delays as long as possible. A terminal
+    /// position (only the poison left) has depth 0.
+    pub fn depth_to_win(&mut self, state: BoardState) -> u32 {
+        if let Some(&depth) = self.depth_cache.get(&state) {
+            return depth;
+        }
+
+        let eval = self.evaluate(state);
+        let depth = if eval.winning {
+            let mut best = u32::MAX;
+            for mv in eval.winning_moves {
+                best = best.min(self.depth_to_win(state.apply_move(mv)));
+            }
+            best + 1
+        } else {
+            let mut longest = None;
+            for mv in self.moves(state) {
+                let next = self.depth_to_win(state.apply_move(mv));
+                longest = Some(longest.map_or(next, |d: u32| d.max(next)));
+            }
+            longest.map_or(0, |d| d + 1)
+        };
+        self.depth_cache.insert(state, depth);
+        depth
+    }
+
+    /// Verdict and [`Solver::depth_to_win`] for every position reachable from
+    /// the full board, solved backward from the terminal positions in one
+    /// pass instead of by forward search. Ignores the cache.
+    pub fn solve_retrograde_with_depth(&self) -> HashMap<BoardState, (Verdict, u32)> {
+        // Forward sweep: each position's predecessors and how many moves it
+        // has that are not yet known to lead to a won position.
+        let start = BoardState::new();
+        let mut predecessors: HashMap<BoardState, Vec<BoardState>> = HashMap::new();
+        let mut pending: HashMap<BoardState, usize> = HashMap::new();
+        predecessors.insert(start, Vec::new());
+        let mut stack = vec![start];
+        while let Some(state) = stack.pop() {
+            let moves = self.moves(state);
+            pending.insert(state, moves.len());
+            for mv in moves {
+                let next = state.apply_move(mv);
+                let parents = predecessors.entry(next).or_default();
+                if parents.is_empty() {
+                    stack.push(next);
+                }
+                parents.push(state);
+            }
+        }
+
+        // Backward sweep in order of depth, so a win is labeled through its
+        // shallowest losing child and a loss once its deepest child is known.
+        let mut solved = HashMap::with_capacity(pending.len());
+        let mut queue: VecDeque<BoardState> = pending
+            .iter()
+            .filter(|&(_, &left)| left == 0)
+            .map(|(&state, _)| state)
+            .collect();
+        for &state in &queue {
+            solved.insert(state, (Verdict::Loss, 0));
+        }
+        while let Some(state) = queue.pop_front() {
+            let (verdict, depth) = solved[&state];
+            for &prev in &predecessors[&state] {
+                if solved.contains_key(&prev) {
+                    continue;
+                }
+                let left = pending
+                    .get_mut(&prev)
+                    .expect("every predecessor was expanded");
+                *left -= 1;
+                let label = match verdict {
+                    Verdict::Loss => Verdict::Win,
+                    Verdict::Win if *left == 0 => Verdict::Loss,
+                    Verdict::Win => continue,
+                };
+                solved.insert(prev, (label, depth + 1));
+                queue.push_back(prev);
+            }
+        }
+        solved
+    }
+
+    /// Principal variation from `state`: the side that is winning takes its
+    /// fastest win and the losing side its longest defense, until only the
+    /// poison remains. Ties go to the earliest move in `legal_moves` order.
+    pub fn fastest_win_line(&mut self, state: BoardState) -> Vec<Move> {
+        let mut line = Vec::new();
+        let mut current = state;
+        while let Some(mv) = self.principal_move(current) {
+            line.push(mv);
+            current = current.apply_move(mv);
+        }
+        line
+    }
+
+    /// The quickest way to lose from a lost `state`: the opponent always takes
+    /// a winning move and both sides pick whatever ends the game soonest.
+    /// Unlike [`Solver::fastest_win_line`], the line ends with the loser
+    /// biting the poison in misère play, so a position with only the poison
+    /// left gives `[POISON]`. Empty if `state` is winning.
+    pub fn shortest_loss_line(&mut self, state: BoardState) -> Vec<Move> {
+        if self.is_winning(state) {
+            return Vec::new();
+        }
+        let mut memo = HashMap::new();
+        let mut line = Vec::new();
+        let mut current = state;
+        loop {
+            let candidates = if self.is_winning(current) {
+                self.evaluate(current).winning_moves
+            } else {
+                self.moves(current)
+            };
+            let mut best: Option<(Move, u32)> = None;
+            for mv in candidates {
+                let plies = self.quickest_end(current.apply_move(mv), &mut memo);
+                if best.is_none_or(|(_, b)| plies < b) {
+                    best = Some((mv, plies));
+                }
+            }
+            let Some((mv, _)) = best else { break };
+            line.push(mv);
+            current = current.apply_move(mv);
+        }
+        if self.mode == GameMode::Misere {
+            line.push(POISON);
+        }
+        line
+    }
+
+    /// Fewest plies until no move is left when the winner only plays winning
+    /// moves and the loser cooperates.
+    fn quickest_end(&mut self, state: BoardState, memo: &mut HashMap<BoardState, u32>) -> u32 {
+        if let Some(&plies) = memo.get(&state) {
+            return plies;
+        }
+        let candidates = if self.is_winning(state) {
+            self.evaluate(state).winning_moves
+        } else {
+            self.moves(state)
+        };
+        let plies = candidates
+            .into_iter()
+            .map(|mv| self.quickest_end(state.apply_move(mv), memo) + 1)
+            .min()
+            .unwrap_or(0);
+        memo.insert(state, plies);
+        plies
+    }
+
+    /// Opcodes for the mover's own moves along [`Solver::fastest_win_line`];
+    /// the opponent's replies are left out since they arrive on-chain.
+    pub fn optimal_opcode_line(&mut self, state: BoardState) -> Vec<u8> {
+        self.fastest_win_line(state)
+            .into_iter()
+            .step_by(2)
+            .map(opcode::encode)
+            .collect()
+    }
+
+    /// Winning moves after which answering every reply with its
+    /// [`Move::mirror`] keeps the win, so the rest of the game needs no
+    /// thought. Only square corners around the poison can qualify.
+    pub fn mirrorable_wins(&mut self, state: BoardState) -> Vec<Move> {
+        self.evaluate(state)
+            .winning_moves
+            .into_iter()
+            .filter(|&mv| mirroring_wins(state.apply_move(mv)))
+            .collect()
+    }
+
+    /// Pairing for a lost position made of two columns of equal height: only
+    /// the last two columns have candies left, and the one left of the poison
+    /// holds as many as the poison column does above the poison.
+    ///
+    /// The side that just moved keeps the win by answering a bite at row `r`
+    /// of one column with a bite at the same depth in the other, which leaves
+    /// two equal columns again. The returned pair is the bottom one; the
+    /// others are the same two moves shifted up together. `None` for any
+    /// other shape.
+    pub fn pairing_partner(&self, state: BoardState) -> Option<(Move, Move)> {
+        let (row, col) = (POISON.row as i8, POISON.col as usize);
+        if col == 0
+            || state.heights[..col - 1]
+                .iter()
+                .any(|&h| h != ROWS as i8 - 1)
+        {
+            return None;
+        }
+        let left = ROWS as i8 - 1 - state.heights[col - 1];
+        let above = row - 1 - state.heights[col];
+        if left == 0 || left != above {
+            return None;
+        }
+        Some((
+            Move::new(ROWS as u8 - 1, col as u8 - 1),
+            Move::new(POISON.row - 1, POISON.col),
+        ))
+    }
+
+    /// Cells the winning side eats in every optimal continuation from `state`,
+    /// in row-major order. The winner may play any winning move and the loser
+    /// any of its longest defenses. Empty when `state` is lost.
+    pub fn forced_cells(&mut self, state: BoardState) -> Vec<Move> {
+        let mut memo = HashMap::new();
+        let mask = self.forced_mask(state, &mut memo);
+        (0..ROWS * COLS)
+            .filter(|bit| mask & (1 << bit) != 0)
+            .map(|bit| Move::new((bit / COLS) as u8, (bit % COLS) as u8))
+            .collect()
+    }
+
+    /// Bitmask (bit `row * COLS + col`) of the cells the side to move is sure
+    /// to eat if it is winning; empty for a lost position.
+    fn forced_mask(&mut self, state: BoardState, memo: &mut HashMap<BoardState, u64>) -> u64 {
+        if let Some(&mask) = memo.get(&state) {
+            return mask;
+        }
+        let mut forced = None;
+        for mv in self.evaluate(state).winning_moves {
+            let next = state.apply_move(mv);
+            let mut after = None;
+            let longest = self.depth_to_win(next).checked_sub(1);
+            for defense in self.moves(next) {
+                let reply = next.apply_move(defense);
+                if Some(self.depth_to_win(reply)) == longest {
+                    let mask = self.forced_mask(reply, memo);
+                    after = Some(after.map_or(mask, |m: u64| m & mask));
+                }
+            }
+            let mask = eaten_mask(state, next) | after.unwrap_or(0);
+            forced = Some(forced.map_or(mask, |m: u64| m & mask));
+        }
+        let mask = forced.unwrap_or(0);
+        memo.insert(state, mask);
+        mask
+    }
+
+    /// The winning move `tiebreak` prefers, or `None` if `state` is lost.
+    pub fn recommend(&mut self, state: BoardState, tiebreak: Tiebreak) -> Option<Move> {
+        let candidates = self.evaluate(state).winning_moves;
+        match tiebreak {
+            Tiebreak::First => candidates.first().copied(),
+            Tiebreak::Fastest => self.pick_by_depth(state, candidates, false),
+            // `max_by_key` keeps the last maximum; reverse so ties go to the earliest move.
+            Tiebreak::MostEaten => candidates
+                .iter()
+                .rev()
+                .max_by_key(|&&mv| state.eaten_count(mv))
+                .copied(),
+        }
+    }
+
+    /// Candy-scoring variant: the most net candies (the mover's bites minus
+    /// the opponent's) the mover can guarantee from `state` until only the
+    /// poison is left, when both sides maximize their own haul. Who is left
+    /// with the poison does not matter here; see [`BoardState::score`].
+    pub fn candy_margin(&mut self, state: BoardState) -> i32 {
+        if let Some(&margin) = self.candy_cache.get(&state) {
+            return margin;
+        }
+        let margin = state
+            .legal_moves()
+            .into_iter()
+            .map(|mv| state.eaten_count(mv) as i32 - self.candy_margin(state.apply_move(mv)))
+            .max()
+            .unwrap_or(0);
+        self.candy_cache.insert(state, margin);
+        margin
+    }
+
+    /// The earliest move in `legal_moves` order that achieves
+    /// [`Solver::candy_margin`], or `None` if only the poison is left.
+    pub fn best_candy_move(&mut self, state: BoardState) -> Option<Move> {
+        let target = self.candy_margin(state);
+        state.legal_moves().into_iter().find(|&mv| {
+            state.eaten_count(mv) as i32 - self.candy_margin(state.apply_move(mv)) == target
+        })
+    }
+
+    /// From a lost position, the move that makes the game last longest.
+    /// `None` if `state` is winning or only the poison is left.
+    pub fn best_defense(&mut self, state: BoardState) -> Option<Move> {
+        if self.is_winning(state) {
+            return None;
+        }
+        let moves = self.moves(state);
+        self.pick_by_depth(state, moves, true)
+    }
+
+    /// How many of their own moves the side to move must make before they can
+    /// hand the opponent a losing position, if the opponent replies as
+    /// helpfully as possible: a breadth-first search over (move, reply) pairs
+    /// until a winning position turns up.
+    ///
+    /// A winning position is 0. From a losing one nothing can be forced, so
+    /// this counts the fewest opponent mistakes' worth of turns, usually 1.
+    /// `usize::MAX` when every line ends on the poison first, e.g. with only
+    /// the poison left.
+    pub fn distance_to_p(&mut self, state: BoardState) -> usize {
+        let mut frontier = vec![state];
+        let mut seen = HashSet::from([state]);
+        let mut distance = 0;
+        loop {
+            if frontier.iter().any(|&board| self.is_winning(board)) {
+                return distance;
+            }
+            let mut next = Vec::new();
+            for board in frontier {
+                for mv in board.legal_moves() {
+                    let theirs = board.apply_move(mv);
+                    for reply in theirs.legal_moves() {
+                        let ours = theirs.apply_move(reply);
+                        if seen.insert(ours) {
+                            next.push(ours);
+                        }
+                    }
+                }
+            }
+            if next.is_empty() {
+                return usize::MAX;
+            }
+            frontier = next;
+            distance += 1;
+        }
+    }
+
+    /// Every legal move from `state`, best first: winning moves in `tiebreak`
+    /// order, then losing moves from the longest defense down. Ties keep
+    /// `legal_moves` order, so the head is [`Solver::recommend`] when winning
+    /// and [`Solver::best_defense`] when losing.
+    pub fn ranked_moves(&mut self, state: BoardState, tiebreak: Tiebreak) -> Vec<Move> {
+        let mut winning = self.evaluate(state).winning_moves;
+        match tiebreak {
+            Tiebreak::First => {}
+            Tiebreak::Fastest => {
+                winning.sort_by_cached_key(|&mv| self.depth_to_win(state.apply_move(mv)))
+            }
+            Tiebreak::MostEaten => {
+                winning.sort_by_key(|&mv| std::cmp::Reverse(state.eaten_count(mv)))
+            }
+        }
+        let mut losing: Vec<Move> = self
+            .moves(state)
+            .into_iter()
+            .filter(|mv| !winning.contains(mv))
+            .collect();
+        losing.sort_by_cached_key(|&mv| std::cmp::Reverse(self.depth_to_win(state.apply_move(mv))));
+        winning.extend(losing);
+        winning
+    }
+
+    /// Pick the candidate whose successor has the smallest (or, with
+    /// `longest`, largest) depth to win; ties go to the earliest candidate.
+    fn pick_by_depth(
+        &mut self,
+        state: BoardState,
+        candidates: Vec<Move>,
+        longest: bool,
+    ) -> Option<Move> {
+        let mut best: Option<(Move, u32)> = None;
+        for mv in candidates {
+            let depth = self.depth_to_win(state.apply_move(mv));
+            let better = match best {
+                None => true,
+                Some((_, d)) if longest => depth > d,
+                Some((_, d)) => depth < d,
+            };
+            if better {
+                best = Some((mv, depth));
+            }
+        }
+        best.map(|(mv, _)| mv)
+    }
+
+    fn principal_move(&mut self, state: BoardState) -> Option<Move> {
+        self.recommend(state, Tiebreak::Fastest)
+            .or_else(|| self.best_defense(state))
+    }
+}
+
+/// Whether mirroring every move from `state`, with the opponent to play,
+/// wins: each reply's mirror must still be on the board.
+fn mirroring_wins(state: BoardState) -> bool {
+    state.legal_moves().into_iter().all(|mv| {
+        let after = state.apply_move(mv);
+        match mv.mirror() {
+            Some(reply) if after.heights[reply.col as usize] < reply.row as i8 => {
+                mirroring_wins(after.apply_move(reply))
+            }
+            _ => false,
+        }
+    })
+}
+
+/// Label each state [`Verdict::Win`] (N) or [`Verdict::Loss`] (P) for the
+/// player to move under misère rules, in input order.
+///
+/// With the `parallel` feature the states are spread over rayon's thread pool,
+/// which shares one concurrent verdict cache; otherwise this is
+/// [`classify_batch_serial`].
+pub fn classify_batch(states: &[BoardState]) -> Vec<Verdict> {
+    #[cfg(feature = "parallel")]
+    return parallel::classify_batch(states);
+    #[cfg(not(feature = "parallel"))]
+    classify_batch_serial(states)
+}
+
+/// [`classify_batch`] on the current thread with a single [`Solver`].
+pub fn classify_batch_serial(states: &[BoardState]) -> Vec<Verdict> {
+    let mut solver = Solver::new();
+    states.iter().map(|&state| solver.verdict(state)).collect()
+}
+
+/// Count reachable positions on which misère and normal play agree or
+/// disagree about whether the mover wins, returned as `(agree, disagree)`.
+pub fn mode_agreement() -> (u64, u64) {
+    let mut misere = Solver::with_mode(GameMode::Misere);
+    let mut normal = Solver::with_mode(GameMode::Normal);
+    let mut agree = 0;
+    let mut disagree = 0;
+    for state in enumerate_states() {
+        if misere.is_winning(state) == normal.is_winning(state) {
+            agree += 1;
+        } else {
+            disagree += 1;
+        }
+    }
+    (agree, disagree)
+}
+
+/// A short printable overview of the solved game: board size, who wins,
+/// the winning opening(s), and how many reachable and losing positions exist.
+pub fn solution_summary() -> String {
+    let mut solver = Solver::new();
+    let openings = winning_openings_algebraic();
+    let states = enumerate_states();
+    let losing = states
+        .iter()
+        .filter(|&&state| !solver.is_winning(state))
+        .count();
+    let verdict = if openings.is_empty() {
+        "second player"
+    } else {
+        "first player"
+    };
+    let opening_label = match openings.len() {
+        1 => "Unique winning opening",
+        _ => "Winning openings",
+    };
+    let unique_sizes = (1..=ROWS)
+        .flat_map(|rows| (1..=COLS).map(move |cols| (rows, cols)))
+        .filter(|&(rows, cols)| has_unique_winning_opening(rows, cols))
+        .count();
+    format!(
+        "{}×{} Chomping Glass is a {} win.\n{}: {}\nOpening moves: {}\nReachable positions: {}\nLosing positions (P-positions): {}\nBoard sizes up to {}×{} with a unique winning opening: {} of {}\n",
+        ROWS,
+        COLS,
+        verdict,
+        opening_label,
+        openings.join(", "),
+        opening_branching_factor(),
+        states.len(),
+        losing,
+        ROWS,
+        COLS,
+        unique_sizes,
+        ROWS * COLS
+    )
+}
+
+/// Number of legal moves from `state`.
+pub fn branching_factor(state: BoardState) -> usize {
+    state.legal_moves().len()
+}
+
+/// Number of possible first moves: every cell except the poison.
+pub fn opening_branching_factor() -> usize {
+    branching_factor(BoardState::new())
+}
+
+/// Reachable losing positions (for the player to move) with exactly
+/// `candies` candies left besides the poison, in enumeration order.
+pub fn losing_positions_with_candies(candies: usize) -> Vec<BoardState> {
+    let mut solver = Solver::new();
+    enumerate_states()
+        .into_iter()
+        .filter(|&state| state.remaining_candies() == candies && !solver.is_winning(state))
+        .collect()
+}
+
+/// Every losing position as a compact string (see [`BoardState::to_compact`]),
+/// one line per remaining-candy count in ascending order, e.g.
+/// `0: 55555554`. Counts without a losing position are left out.
+pub fn p_position_cheatsheet() -> String {
+    // One pass instead of calling `losing_positions_with_candies` per count.
+    let mut solver = Solver::new();
+    let mut groups: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+    for state in enumerate_states() {
+        if !solver.is_winning(state) {
+            groups
+                .entry(state.remaining_candies())
+                .or_default()
+                .push(state.to_compact());
+        }
+    }
+    let mut out = String::new();
+    for (candies, mut shapes) in groups {
+        shapes.sort();
+        out.push_str(&format!("{}: {}\n", candies, shapes.join(" ")));
+    }
+    out
+}
+
+/// Reachable winning positions with exactly one winning move, in
+/// enumeration order. These are the sharpest decisions in the game.
+pub fn unique_winning_move_positions() -> Vec<BoardState> {
+    let mut solver = Solver::new();
+    enumerate_states()
+        .into_iter()
+        .filter(|&state| solver.evaluate(state).winning_moves.len() == 1)
+        .collect()
+}
+
+/// The reachable winning position with the longest forced win, and its
+/// [`Solver::depth_to_win`]. Ties go to the smallest board in [`BoardState`]
+/// order.
+pub fn deepest_winning_position() -> (BoardState, u32) {
+    let mut solver = Solver::new();
+    let mut states: Vec<BoardState> = enumerate_states().into_iter().collect();
+    states.sort();
+    let mut deepest: Option<(BoardState, u32)> = None;
+    for state in states {
+        if !solver.is_winning(state) {
+            continue;
+        }
+        let depth = solver.depth_to_win(state);
+        if deepest.is_none_or(|(_, best)| depth > best) {
+            deepest = Some((state, depth));
+        }
+    }
+    deepest.expect("the full board is a winning position")
+}
+
+/// Reachable winning positions grouped by how many winning moves they have,
+/// each group sorted. Count 1 holds the hardest positions to find the win in.
+pub fn positions_by_winning_move_count() -> BTreeMap<usize, Vec<BoardState>> {
+    let mut solver = Solver::new();
+    let mut groups: BTreeMap<usize, Vec<BoardState>> = BTreeMap::new();
+    for state in enumerate_states() {
+        let winning = solver
+            .analyze_moves(state)
+            .iter()
+            .filter(|analysis| analysis.winning)
+            .count();
+        if winning > 0 {
+            groups.entry(winning).or_default().push(state);
+        }
+    }
+    for states in groups.values_mut() {
+        states.sort();
+    }
+    groups
+}
+
+/// Reachable winning positions that open a forcing sequence at least
+/// `plies` plies long, in enumeration order: the winner always has exactly
+/// one winning move and the loser exactly one longest-resisting reply.
+///
+/// The loser's side is judged by its best defense because a literal single
+/// legal move never follows a win: the last candy hands the poison back. A
+/// game that ends before `plies` plies does not count. On the 5×8 board the
+/// longest such sequences are three plies.
+pub fn fully_forced_positions(plies: usize) -> Vec<BoardState> {
+    fn forced(solver: &mut Solver, state: BoardState, plies: usize) -> bool {
+        if plies == 0 {
+            return true;
+        }
+        let candidates = if solver.is_winning(state) {
+            solver.evaluate(state).winning_moves
+        } else {
+            let moves = state.legal_moves();
+            let depths: Vec<u32> = moves
+                .iter()
+                .map(|&mv| solver.depth_to_win(state.apply_move(mv)))
+                .collect();
+            let longest = depths.iter().copied().max();
+            moves
+                .into_iter()
+                .zip(depths)
+                .filter(|&(_, depth)| Some(depth) == longest)
+                .map(|(mv, _)| mv)
+                .collect()
+        };
+        match candidates[..] {
+            [only] => forced(solver, state.apply_move(only), plies - 1),
+            _ => false,
+        }
+    }
+
+    let mut solver = Solver::new();
+    enumerate_states()
+        .into_iter()
+        .filter(|&state| solver.is_winning(state) && forced(&mut solver, state, plies))
+        .collect()
+}
+
+/// Number of distinct complete move sequences from `state` until only the
+/// poison is left. A terminal position counts as one (empty) game.
+///
+/// Counts are memoized per state and saturate at `u128::MAX` rather than
+/// overflow, so a result of `u128::MAX` means "at least this many".
+pub fn distinct_games(state: BoardState) -> u128 {
+    fn count(state: BoardState, memo: &mut HashMap<BoardState, u128>) -> u128 {
+        if let Some(&n) = memo.get(&state) {
+            return n;
+        }
+        let moves = state.legal_moves();
+        let n = if moves.is_empty() {
+            1
+        } else {
+            moves.into_iter().fold(0u128, |acc, mv| {
+                acc.saturating_add(count(state.apply_move(mv), memo))
+            })
+        };
+        memo.insert(state, n);
+        n
+    }
+    count(state, &mut HashMap::new())
+}
+
+/// Chance of winning from `state`, with us to move and playing to maximize
+/// it, against an opponent who picks uniformly among their legal moves.
+pub fn random_opponent_winprob(state: BoardState) -> f64 {
+    random_opponent_winprob_weighted(state, |_, _| 1.0)
+}
+
+/// Like [`random_opponent_winprob`], but the opponent plays each legal move
+/// with probability proportional to `weight_fn(board, move)`. Weights must be
+/// non-negative; if every move from a board weighs zero, the opponent picks
+/// uniformly there.
+pub fn random_opponent_winprob_weighted<F>(state: BoardState, weight_fn: F) -> f64
+where
+    F: Fn(&BoardState, Move) -> f64,
+{
+    fn ours<F: Fn(&BoardState, Move) -> f64>(
+        state: BoardState,
+        weight_fn: &F,
+        memo: &mut HashMap<(BoardState, bool), f64>,
+    ) -> f64 {
+        if let Some(&p) = memo.get(&(state, true)) {
+            return p;
+        }
+        // Only the poison left: we have to take it.
+        let p = state
+            .legal_moves()
+            .into_iter()
+            .map(|mv| theirs(state.apply_move(mv), weight_fn, memo))
+            .fold(0.0, f64::max);
+        memo.insert((state, true), p);
+        p
+    }
+
+    fn theirs<F: Fn(&BoardState, Move) -> f64>(
+        state: BoardState,
+        weight_fn: &F,
+        memo: &mut HashMap<(BoardState, bool), f64>,
+    ) -> f64 {
+        if let Some(&p) = memo.get(&(state, false)) {
+            return p;
+        }
+        let moves = state.legal_moves();
+        let p = if moves.is_empty() {
+            1.0
+        } else {
+            let mut weights: Vec<f64> = moves.iter().map(|&mv| weight_fn(&state, mv)).collect();
+            if weights.iter().sum::<f64>() <= 0.0 {
+                weights = vec![1.0; moves.len()];
+            }
+            let total: f64 = weights.iter().sum();
+            moves
+                .iter()
+                .zip(&weights)
+                .map(|(&mv, &w)| w / total * ours(state.apply_move(mv), weight_fn, memo))
+                .sum()
+        };
+        memo.insert((state, false), p);
+        p
+    }
+
+    ours(state, &weight_fn, &mut HashMap::new())
+}
+
+/// How much the game tree collapses into a DAG, from [`transposition_stats`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TranspositionStats {
+    /// Distinct boards reached within the depth bound, including the start.
+    pub distinct_states: usize,
+    /// Move sequences (including the empty one) within the depth bound,
+    /// saturating at `u128::MAX`.
+    pub sequences: u128,
+    /// Most sequences that lead to any single board.
+    pub max_sequences_per_state: u128,
+}
+
+impl TranspositionStats {
+    /// Average number of move orders that reach each board.
+    pub fn average_sequences_per_state(&self) -> f64 {
+        self.sequences as f64 / self.distinct_states as f64
+    }
+}
+
+/// Walk the tree from `state` ply by ply for up to `max_depth` plies, counting
+/// how many move sequences reach each board. A board reached at several depths
+/// adds up its sequences from every layer.
+pub fn transposition_stats(state: BoardState, max_depth: usize) -> TranspositionStats {
+    let mut totals: HashMap<BoardState, u128> = HashMap::from([(state, 1)]);
+    let mut layer = totals.clone();
+    for _ in 0..max_depth {
+        let mut next: HashMap<BoardState, u128> = HashMap::new();
+        for (board, paths) in &layer {
+            for mv in board.legal_moves() {
+                let slot = next.entry(board.apply_move(mv)).or_default();
+                *slot = slot.saturating_add(*paths);
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        for (board, paths) in &next {
+            let slot = totals.entry(*board).or_default();
+            *slot = slot.saturating_add(*paths);
+        }
+        layer = next;
+    }
+    TranspositionStats {
+        distinct_states: totals.len(),
+        sequences: totals.values().fold(0u128, |acc, &n| acc.saturating_add(n)),
+        max_sequences_per_state: totals.values().copied().max().unwrap_or(0),
+    }
+}
+
+/// Fewest and most plies any game from the full board can last, counting
+/// every line of play rather than only optimal ones, as `(min, max)`.
+pub fn game_length_bounds() -> (usize, usize) {
+    fn bounds(state: BoardState, memo: &mut HashMap<BoardState, (usize, usize)>) -> (usize, usize) {
+        if let Some(&known) = memo.get(&state) {
+            return known;
+        }
+        let found = state
+            .legal_moves()
+            .into_iter()
+            .map(|mv| bounds(state.apply_move(mv), memo))
+            .fold(None, |acc: Option<(usize, usize)>, (lo, hi)| {
+                Some(acc.map_or((lo, hi), |(min, max)| (min.min(lo), max.max(hi))))
+            })
+            .map_or((0, 0), |(min, max)| (min + 1, max + 1));
+        memo.insert(state, found);
+        found
+    }
+    bounds(BoardState::new(), &mut HashMap::new())
+}
+
+/// Every winning first move on the full board in algebraic notation, in
+/// `legal_moves` order: `["b1"]` for 5×8.
+pub fn winning_openings_algebraic() -> Vec<String> {
+    Solver::new()
+        .analyze_moves(BoardState::new())
+        .into_iter()
+        .filter(|analysis| analysis.winning)
+        .map(|analysis| notation::to_algebraic(analysis.mv))
+        .collect()
+}
+
+/// Whether a fresh `rows × cols` board (see [`BoardState::corner`]) has
+/// exactly one winning first move. Every size up to 5×8 does except 1×1,
+/// where the only move is the poison.
+///
+/// Panics if the board does not fit in 5×8.
+pub fn has_unique_winning_opening(rows: usize, cols: usize) -> bool {
+    let start = BoardState::corner(rows, cols).expect("board size must fit in 5×8");
+    Solver::new()
+        .analyze_moves(start)
+        .iter()
+        .filter(|analysis| analysis.winning)
+        .count()
+        == 1
+}
+
+/// Full policy of a smaller board, from [`solve_subboard`].
+#[derive(Clone, Debug, Default)]
+pub struct SubboardPolicy {
+    pub rows: usize,
+    pub cols: usize,
+    /// Every state reachable from the sub-board's opening. Boards and moves
+    /// are in full-board coordinates, with the sub-board in the poison corner.
+    pub table: BTreeMap<BoardState, Evaluation>,
+}
+
+impl SubboardPolicy {
+    /// The sub-board before any move.
+    pub fn start(&self) -> BoardState {
+        BoardState::corner(self.rows, self.cols).expect("solved sizes fit in 5×8")
+    }
+
+    /// Evaluation of the opening position.
+    pub fn opening(&self) -> &Evaluation {
+        &self.table[&self.start()]
+    }
+}
+
+/// Solve a `rows × cols` board (see [`BoardState::corner`]) and return the
+/// evaluation of every position reachable from it.
+///
+/// Panics if the board does not fit in 5×8.
+pub fn solve_subboard(rows: usize, cols: usize) -> SubboardPolicy {
+    let start = BoardState::corner(rows, cols).expect("board size must fit in 5×8");
+    let mut solver = Solver::new();
+    let mut table = BTreeMap::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(state) = queue.pop_front() {
+        if table.contains_key(&state) {
+            continue;
+        }
+        queue.extend(
+            state
+                .legal_moves()
+                .into_iter()
+                .map(|mv| state.apply_move(mv)),
+        );
+        table.insert(state, solver.evaluate(state));
+    }
+    SubboardPolicy { rows, cols, table }
+}
+
+/// Every reachable state with its evaluation, in canonical order.
+pub fn policy_table() -> BTreeMap<BoardState, Evaluation> {
+    let mut table = BTreeMap::new();
+    for_each_policy_entry(|state, eval| {
+        table.insert(state, eval.clone());
+    });
+    table
+}
+
+/// Call `f` with every reachable state and its evaluation, in [`BoardState`]
+/// order, sharing one warm [`Solver`] instead of building a table.
+pub fn for_each_policy_entry<F>(mut f: F)
+where
+    F: FnMut(BoardState, &Evaluation),
+{
+    let mut solver = Solver::new();
+    let mut states = enumerate_states();
+    states.sort();
+    for state in states {
+        let eval = solver.evaluate(state);
+        f(state, &eval);
+    }
+}
+
+/// Stable 64-bit fingerprint of the full policy, for cheap regression checks.
+pub fn policy_fingerprint() -> u64 {
+    fingerprint_table(&policy_table())
+}
+
+/// FNV-1a over each `(state, evaluation)` pair in the table's order. Unlike
+/// `std`'s `DefaultHasher`, the result does not change across Rust releases.
+pub fn fingerprint_table(table: &BTreeMap<BoardState, Evaluation>) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    let mut hash = OFFSET;
+    let mut feed = |byte: u8| {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(PRIME);
+    };
+    for (state, eval) in table {
+        for &h in state.heights() {
+            feed(h as u8);
+        }
+        feed(u8::from(eval.winning));
+        feed(eval.winning_moves.len() as u8);
+        for mv in &eval.winning_moves {
+            feed(mv.row);
+            feed(mv.col);
+        }
+    }
+    hash
+}
+
+/// Errors raised while enumerating the state space.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum EnumerationError {
+    #[error("state limit exceeded: discovered more than {limit} states")]
+    LimitExceeded { limit: usize },
+}
+
+/// Enumerate every reachable board state via BFS.
+pub fn enumerate_states() -> Vec<BoardState> {
+    enumerate_states_limited(usize::MAX).expect("unbounded enumeration cannot hit its limit")
+}
+
+/// Number of reachable boards counted without playing any moves: staircases
+/// of per-column eaten counts that fit in the box and spare the poison. This
+/// is the lattice-path count `C(ROWS + COLS, ROWS)` less the fully eaten board.
+fn staircase_bound() -> usize {
+    // ways[e]: staircases over the columns so far whose last column has e eaten.
+    let mut ways = [1usize; ROWS + 1];
+    for _ in 1..COLS {
+        let mut next = [0usize; ROWS + 1];
+        for (eaten, slot) in next.iter_mut().enumerate() {
+            *slot = ways[eaten..].iter().sum();
+        }
+        ways = next;
+    }
+    // The poison column may not be eaten to the bottom.
+    ways[..ROWS].iter().sum()
+}
+
+/// Whether [`enumerate_states`] finds exactly as many boards as
+/// [`staircase_bound`] predicts, guarding against moves that drop or invent states.
+pub fn reachable_state_count_matches_bound() -> bool {
+    enumerate_states().len() == staircase_bound()
+}
+
+/// Like [`enumerate_states`], but aborts once more than `max` states are discovered.
+pub fn enumerate_states_limited(max: usize) -> Result<Vec<BoardState>, EnumerationError> {
+    let start = BoardState::new();
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::new();
+    seen.insert(start);
+    queue.push_back(start);
+    if seen.len() > max {
+        return Err(EnumerationError::LimitExceeded { limit: max });
+    }
+
+    while let Some(state) = queue.pop_front() {
+        for mv in state.legal_moves() {
+            let next = state.apply_move(mv);
+            if seen.insert(next) {
+                if seen.len() > max {
+                    return Err(EnumerationError::LimitExceeded { limit: max });
+                }
+                queue.push_back(next);
+            }
+        }
+    }
+
+    Ok(seen.into_iter().collect())
+}
+
+/// How many evaluated states pass between progress callbacks.
+const PROGRESS_INTERVAL: usize = 256;
+
+/// Export the complete policy table to disk as a [`PolicyFile`].
+pub fn export_policy_json<P: AsRef<Path>>(path: P) -> anyhow::Result<()> {
+    export_policy_json_with_progress(path, |_, _| {})
+}
+
+/// Like [`export_policy_json`], calling `progress(done, total)` every few
+/// hundred evaluated states and once more when `done == total`.
+pub fn export_policy_json_with_progress<P, F>(path: P, progress: F) -> anyhow::Result<()>
+where
+    P: AsRef<Path>,
+    F: FnMut(usize, usize),
+{
+    export_policy_json_resumable(
+        path,
+        false,
+        PolicyKeyFormat::Heights,
+        PolicyMoveFormat::Structured,
+        progress,
+        || false,
+    )?;
+    Ok(())
+}
+
+/// How states are keyed in an exported policy table.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum PolicyKeyFormat {
+    /// The debug-printed heights array, e.g. `"[0, 0, -1, -1, -1, -1, -1, -1]"`.
+    #[default]
+    Heights,
+    /// The decimal [`BoardState::to_u64`] packing, e.g. `"18"`.
+    U64,
+}
+
+impl PolicyKeyFormat {
+    pub fn key(self, state: &BoardState) -> String {
+        match self {
+            PolicyKeyFormat::Heights => format!("{:?}", state.heights),
+            PolicyKeyFormat::U64 => state.to_u64().to_string(),
+        }
+    }
+
+    /// Parse a key written in either format.
+    pub fn parse_key(raw: &str) -> Option<BoardState> {
+        if let Ok(packed) = raw.parse::<u64>() {
+            return BoardState::from_u64(packed);
+        }
+        let inner = raw.strip_prefix('[')?.strip_suffix(']')?;
+        let values: Vec<i8> = inner
+            .split(',')
+            .map(|v| v.trim().parse().ok())
+            .collect::<Option<_>>()?;
+        let heights: [i8; COLS] = values.try_into().ok()?;
+        let state = BoardState { heights };
+        state.is_reachable().then_some(state)
+    }
+}
+
+impl FromStr for PolicyKeyFormat {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        match raw {
+            "heights" => Ok(PolicyKeyFormat::Heights),
+            "u64" => Ok(PolicyKeyFormat::U64),
+            other => Err(format!(
+                "unknown key format {:?} (expected heights or u64)",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for PolicyKeyFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PolicyKeyFormat::Heights => "heights",
+            PolicyKeyFormat::U64 => "u64",
+        })
+    }
+}
+
+/// How moves are written in an exported policy table.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum PolicyMoveFormat {
+    /// Zero-indexed `{"row": 0, "col": 1}` objects, as [`Evaluation`] serializes.
+    #[default]
+    Structured,
+    /// Algebraic strings such as `"b1"`, via [`AlgebraicEvaluation`].
+    Algebraic,
+}
+
+impl FromStr for PolicyMoveFormat {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        match raw {
+            "structured" => Ok(PolicyMoveFormat::Structured),
+            "algebraic" => Ok(PolicyMoveFormat::Algebraic),
+            other => Err(format!(
+                "unknown move format {:?} (expected structured or algebraic)",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for PolicyMoveFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PolicyMoveFormat::Structured => "structured",
+            PolicyMoveFormat::Algebraic => "algebraic",
+        })
+    }
+}
+
+/// A policy table value in either [`PolicyMoveFormat`].
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PolicyEntry {
+    Structured(Evaluation),
+    Algebraic(AlgebraicEvaluation),
+}
+
+impl From<PolicyEntry> for Evaluation {
+    fn from(entry: PolicyEntry) -> Self {
+        match entry {
+            PolicyEntry::Structured(eval) | PolicyEntry::Algebraic(AlgebraicEvaluation(eval)) => {
+                eval
+            }
+        }
+    }
+}
+
+/// Read a table written by [`export_policy_json`], with keys in either
+/// [`PolicyKeyFormat`] and moves in either [`PolicyMoveFormat`]. Both the
+/// current [`PolicyFile`] envelope and bare version 1 tables are accepted.
+pub fn load_policy_json<P: AsRef<Path>>(
+    path: P,
+) -> anyhow::Result<BTreeMap<BoardState, Evaluation>> {
+    let file = std::fs::File::open(path)?;
+    let mut value: serde_json::Value = serde_json::from_reader(std::io::BufReader::new(file))?;
+    let raw: BTreeMap<String, PolicyEntry> = match policy_version(&value)? {
+        1 => serde_json::from_value(value)?,
+        _ => serde_json::from_value(value["states"].take())?,
+    };
+    raw.into_iter()
+        .map(|(key, entry)| {
+            PolicyKeyFormat::parse_key(&key)
+                .map(|state| (state, entry.into()))
+                .ok_or_else(|| anyhow::anyhow!("unrecognized policy key {:?}", key))
+        })
+        .collect()
+}
+
+/// Policy file version written by [`export_policy_json`] and
+/// [`migrate_policy`]. Version 1 is a bare table of [`Evaluation`]s, which
+/// carries no version field.
+pub const POLICY_FORMAT_VERSION: u32 = 2;
+
+/// The format version of a parsed policy file, failing on versions this build
+/// cannot read.
+fn policy_version(value: &serde_json::Value) -> anyhow::Result<u64> {
+    match value.get("version").map(serde_json::Value::as_u64) {
+        None => Ok(1),
+        Some(Some(version)) if version == u64::from(POLICY_FORMAT_VERSION) => Ok(version),
+        Some(_) => anyhow::bail!("unsupported policy format version {}", value["version"]),
+    }
+}
+
+/// Whether the player to move wins ([`PositionType::N`]) or loses
+/// ([`PositionType::P`]) under perfect play.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+pub enum PositionType {
+    N,
+    P,
+}
+
+/// A version 2 policy value: the evaluation plus fields derived from it.
+/// `E` is [`AlgebraicEvaluation`] in tables exported with algebraic moves.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PolicyRecord<E = Evaluation> {
+    #[serde(flatten)]
+    pub evaluation: E,
+    /// See [`Solver::depth_to_win`].
+    pub depth_to_win: u32,
+    pub position_type: PositionType,
+}
+
+impl PolicyRecord {
+    /// Fill in the derived fields for `state`, whose evaluation is `evaluation`.
+    pub fn derive(solver: &mut Solver, state: BoardState, evaluation: Evaluation) -> Self {
+        PolicyRecord {
+            depth_to_win: solver.depth_to_win(state),
+            position_type: if evaluation.winning {
+                PositionType::N
+            } else {
+                PositionType::P
+            },
+            evaluation,
+        }
+    }
+}
+
+impl<E> PolicyRecord<E> {
+    /// Same record with the evaluation converted, e.g. into [`AlgebraicEvaluation`].
+    pub fn map<F>(self, f: impl FnOnce(E) -> F) -> PolicyRecord<F> {
+        PolicyRecord {
+            evaluation: f(self.evaluation),
+            depth_to_win: self.depth_to_win,
+            position_type: self.position_type,
+        }
+    }
+}
+
+/// A version 2 policy file: the table [`export_policy_json`] writes, or a
+/// version 1 table after [`migrate_policy`], with keys in either
+/// [`PolicyKeyFormat`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PolicyFile {
+    pub version: u32,
+    pub states: BTreeMap<String, PolicyRecord>,
+}
+
+/// Upgrade a policy file to [`POLICY_FORMAT_VERSION`], keeping its entries
+/// and their moves and computing only the fields the new version adds. Each
+/// entry's verdict and depth still cost a (cached) solve, so this saves the
+/// full re-export rather than the search. Keys keep their format; moves are
+/// written structured. A file that is already current comes back unchanged.
+///
+/// Fails if a key is unrecognized or an entry's verdict disagrees with the
+/// solver, since the table is then stale or corrupt.
+pub fn migrate_policy(old_json: &str) -> anyhow::Result<String> {
+    let value: serde_json::Value = serde_json::from_str(old_json)?;
+    if policy_version(&value)? == u64::from(POLICY_FORMAT_VERSION) {
+        return Ok(old_json.to_string());
+    }
+
+    let raw: BTreeMap<String, PolicyEntry> = serde_json::from_value(value)?;
+    let mut solver = Solver::new();
+    let mut states = BTreeMap::new();
+    for (key, entry) in raw {
+        let state = PolicyKeyFormat::parse_key(&key)
+            .ok_or_else(|| anyhow::anyhow!("unrecognized policy key {:?}", key))?;
+        let evaluation: Evaluation = entry.into();
+        if evaluation.winning != solver.is_winning(state) {
+            anyhow::bail!("policy entry {} has the wrong verdict", key);
+        }
+        states.insert(key, PolicyRecord::derive(&mut solver, state, evaluation));
+    }
+    Ok(serde_json::to_string(&PolicyFile {
+        version: POLICY_FORMAT_VERSION,
+        states,
+    })?)
+}
+
+/// Bytes per [`export_response_map`] record: a little-endian u64 key and an
+/// opcode.
+const RESPONSE_RECORD_LEN: usize = 9;
+
+/// Write one move per reachable state for bots that only need to know what
+/// to play: `(key, opcode)` records in key order, where the key is
+/// [`BoardState::to_u64`] and the opcode is from [`opcode::encode`]. Winning
+/// states store the move `tiebreak` recommends, losing ones the
+/// [`Solver::best_defense`]. The terminal board has no move and is left out.
+pub fn export_response_map<P: AsRef<Path>>(path: P, tiebreak: Tiebreak) -> anyhow::Result<()> {
+    let mut solver = Solver::new();
+    let mut states: Vec<BoardState> = enumerate_states().into_iter().collect();
+    states.sort_by_key(BoardState::to_u64);
+    let mut bytes = Vec::with_capacity(states.len() * RESPONSE_RECORD_LEN);
+    for state in states {
+        let Some(mv) = solver
+            .recommend(state, tiebreak)
+            .or_else(|| solver.best_defense(state))
+        else {
+            continue;
+        };
+        bytes.extend_from_slice(&state.to_u64().to_le_bytes());
+        bytes.push(opcode::encode(mv));
+    }
+    std::fs::write(path, bytes)?;
+    Ok(())
+}
+
+/// Read a file written by [`export_response_map`].
+pub fn load_response_map<P: AsRef<Path>>(path: P) -> anyhow::Result<BTreeMap<u64, u8>> {
+    let bytes = std::fs::read(path)?;
+    if bytes.len() % RESPONSE_RECORD_LEN != 0 {
+        anyhow::bail!(
+            "response map length {} is not a multiple of {}",
+            bytes.len(),
+            RESPONSE_RECORD_LEN
+        );
+    }
+    Ok(bytes
+        .chunks_exact(RESPONSE_RECORD_LEN)
+        .map(|record| {
+            let key = u64::from_le_bytes(record[..8].try_into().expect("8-byte key"));
+            (key, record[8])
+        })
+        .collect())
+}
+
+/// One move in the tree built by [`optimal_tree`], shaped for front-end tree
+/// visualizers: the board it is played on, the move in algebraic notation,
+/// the verdict for the player making it, and the moves that can follow.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct OptimalTreeNode {
+    pub board: [i8; COLS],
+    #[serde(rename = "move")]
+    pub mv: String,
+    /// For the player making `mv`, before they make it.
+    pub verdict: Verdict,
+    pub children: Vec<OptimalTreeNode>,
+}
+
+/// The optimal-play tree from `state`, at most `max_depth` moves deep.
+///
+/// A winning side plays only its [`Tiebreak::First`] recommendation; a
+/// losing side branches into every legal move, so the tree shows the winner's
+/// answer to each defense. The roots are the moves from `state` itself: one
+/// when it is winning, every legal move when it is losing, and none if
+/// `max_depth` is 0 or only the poison is left.
+pub fn optimal_tree(state: BoardState, max_depth: usize) -> Vec<OptimalTreeNode> {
+    fn nodes(solver: &mut Solver, state: BoardState, depth: usize) -> Vec<OptimalTreeNode> {
+        if depth == 0 {
+            return Vec::new();
+        }
+        let (moves, verdict) = match solver.recommend(state, Tiebreak::First) {
+            Some(mv) => (vec![mv], Verdict::Win),
+            None => (state.legal_moves(), Verdict::Loss),
+        };
+        moves
+            .into_iter()
+            .map(|mv| OptimalTreeNode {
+                board: state.heights,
+                mv: notation::to_algebraic(mv),
+                verdict,
+                children: nodes(solver, state.apply_move(mv), depth - 1),
+            })
+            .collect()
+    }
+
+    nodes(&mut Solver::new(), state, max_depth)
+}
+
+/// Write the [`optimal_tree`] from the full board, `max_depth` moves deep,
+/// as a JSON array of its roots.
+pub fn export_optimal_tree_json<P: AsRef<Path>>(path: P, max_depth: usize) -> anyhow::Result<()> {
+    anyhow::ensure!(max_depth > 0, "a tree needs a depth of at least 1");
+    let tree = optimal_tree(BoardState::new(), max_depth);
+    let file = std::fs::File::create(path)?;
+    serde_json::to_writer(std::io::BufWriter::new(file), &tree)?;
+    Ok(())
+}
+
+/// Sidecar file next to a policy export that records how far it got.
+pub fn export_marker_path<P: AsRef<Path>>(path: P) -> std::path::PathBuf {
+    let mut marker = path.as_ref().as_os_str().to_owned();
+    marker.push(".progress");
+    marker.into()
+}
+
+/// Resumable [`export_policy_json_with_progress`]. States are written one per
+/// line in [`BoardState`] order inside the [`PolicyFile`] envelope, and the sidecar at [`export_marker_path`]
+/// holds the file length and key of the last entry known to be on disk.
+///
+/// `cancel` is polled before each state; returning `true` stops the export,
+/// leaving a marker behind, and this returns `Ok(false)`. With `resume`, an
+/// existing marker is honored: the file is cut back to the recorded length and
+/// writing continues after the recorded state. Without a marker, or without
+/// `resume`, the export starts over. Returns `Ok(true)` once the file is
+/// complete, at which point the marker is removed.
+pub fn export_policy_json_resumable<P, F, C>(
+    path: P,
+    resume: bool,
+    key_format: PolicyKeyFormat,
+    move_format: PolicyMoveFormat,
+    mut progress: F,
+    mut cancel: C,
+) -> anyhow::Result<bool>
+where
+    P: AsRef<Path>,
+    F: FnMut(usize, usize),
+    C: FnMut() -> bool,
+{
+    use std::io::{Seek, SeekFrom, Write};
+
+    let path = path.as_ref();
+    let marker_path = export_marker_path(path);
+    let mut states = enumerate_states();
+    states.sort();
+    let total = states.len();
+    let key = |state: &BoardState| key_format.key(state);
+
+    let marker = if resume {
+        read_export_marker(&marker_path)?
+    } else {
+        None
+    };
+    let (mut file, mut start) = match marker {
+        Some((len, last)) => {
+            let index = states
+                .iter()
+                .position(|state| key(state) == last)
+                .ok_or_else(|| anyhow::anyhow!("progress marker names unknown state {}", last))?;
+            let mut file = std::fs::OpenOptions::new().write(true).open(path)?;
+            file.set_len(len)?;
+            file.seek(SeekFrom::End(0))?;
+            (file, index + 1)
+        }
+        None => {
+            let mut file = std::fs::File::create(path)?;
+            write!(
+                file,
+                "{{\"version\": {}, \"states\": {{",
+                POLICY_FORMAT_VERSION
+            )?;
+            (file, 0)
+        }
+    };
+
+    let mut solver = Solver::new();
+    while start < total {
+        if cancel() {
+            write_export_marker(&marker_path, &mut file, start, &states, key)?;
+            return Ok(false);
+        }
+        let state = states[start];
+        let eval = solver.evaluate(state);
+        let record = PolicyRecord::derive(&mut solver, state, eval);
+        let value = match move_format {
+            PolicyMoveFormat::Structured => serde_json::to_string(&record)?,
+            PolicyMoveFormat::Algebraic => serde_json::to_string(&record.map(AlgebraicEvaluation))?,
+        };
+        let separator = if start == 0 { "\n" } else { ",\n" };
+        write!(file, "{}  \"{}\": {}", separator, key(&state), value)?;
+        start += 1;
+        if start % PROGRESS_INTERVAL == 0 || start == total {
+            progress(start, total);
+            write_export_marker(&marker_path, &mut file, start, &states, key)?;
+        }
+    }
+    file.write_all(b"\n}}\n")?;
+    file.flush()?;
+    if marker_path.exists() {
+        std::fs::remove_file(&marker_path)?;
+    }
+    Ok(true)
+}
+
+/// Record that the first `written` states are on disk; nothing if none are.
+fn write_export_marker(
+    marker_path: &Path,
+    file: &mut std::fs::File,
+    written: usize,
+    states: &[BoardState],
+    key: impl Fn(&BoardState) -> String,
+) -> anyhow::Result<()> {
+    use std::io::Write;
+
+    if written == 0 {
+        return Ok(());
+    }
+    file.flush()?;
+    file.sync_data()?;
+    let len = file.metadata()?.len();
+    std::fs::write(
+        marker_path,
+        format!("{} {}\n", len, key(&states[written - 1])),
+    )?;
+    Ok(())
+}
+
+fn read_export_marker(marker_path: &Path) -> anyhow::Result<Option<(u64, String)>> {
+    let raw = match std::fs::read_to_string(marker_path) {
+        Ok(raw) => raw,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    let (len, last) = raw
+        .trim()
+        .split_once(' ')
+        .ok_or_else(|| anyhow::anyhow!("malformed progress marker {:?}", raw))?;
+    Ok(Some((len.parse()?, last.to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::book;
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    /// Counts allocations made on threads that opted in with [`count_allocations`].
+    struct CountingAlloc;
+
+    thread_local! {
+        static COUNTING: Cell<bool> = const { Cell::new(false) };
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAlloc {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            if COUNTING.with(Cell::get) {
+                ALLOCATIONS.with(|n| n.set(n.get() + 1));
+            }
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: CountingAlloc = CountingAlloc;
+
+    fn count_allocations(f: impl FnOnce()) -> usize {
+        ALLOCATIONS.with(|n| n.set(0));
+        COUNTING.with(|c| c.set(true));
+        f();
+        COUNTING.with(|c| c.set(false));
+        ALLOCATIONS.with(Cell::get)
+    }
+
+    #[test]
+    fn unique_winning_opening() {
+        let mut solver = Solver::new();
+        let start = BoardState::new();
+        let eval = solver.evaluate(start);
+        assert!(eval.winning);
+        let moves: Vec<(u8, u8)> = eval.winning_moves.iter().map(|m| m.to_tuple()).collect();
+        assert_eq!(moves, vec![(0, 1)]);
+    }
+
+    #[test]
+    fn every_reply_to_the_opening_loses() {
+        let mut solver = Solver::new();
+        let start = BoardState::new();
+        let replies = solver.opponent_replies(start, Move::new(0, 1));
+        assert_eq!(
+            replies.len(),
+            start.apply_move(Move::new(0, 1)).legal_moves().len()
+        );
+        assert!(replies.iter().all(|&(_, verdict)| verdict == Verdict::Loss));
+
+        // A losing first move leaves the opponent a winning reply.
+        let replies = solver.opponent_replies(start, Move::new(0, 0));
+        assert!(replies.iter().any(|&(_, verdict)| verdict == Verdict::Win));
+    }
+
+    #[test]
+    fn second_move_book_matches_notes() {
+        let mut solver = Solver::new();
+        let start = BoardState::new().apply_move(Move::new(0, 1));
+
+        let ai_responses: Vec<(Move, Vec<Move>)> = book::known_opening_book()
+            .into_iter()
+            .filter(|(line, _)| line.len() == 2)
+            .map(|(line, answers)| (line[1], answers))
+            .collect();
+        assert_eq!(
+            ai_responses,
+            vec![
+                (Move::new(1, 0), vec![Move::new(0, 4)]),
+                (Move::new(0, 2), vec![Move::new(3, 1)]),
+                (Move::new(2, 0), vec![Move::new(1, 2), Move::new(0, 3)]),
+                (Move::new(3, 0), vec![Move::new(2, 7)]),
+                (Move::new(4, 0), vec![Move::new(3, 5)]),
+            ]
+        );
+
+        for (ai_move, expected) in ai_responses {
+            let state = start.apply_move(ai_move);
+            let eval = solver.evaluate(state);
+            assert!(eval.winning);
+            let mut got: Vec<(u8, u8)> =
+                eval.winning_moves.iter().map(|mv| mv.to_tuple()).collect();
+            got.sort();
+            let mut expected_sorted: Vec<(u8, u8)> =
+                expected.into_iter().map(|mv| mv.to_tuple()).collect();
+            expected_sorted.sort();
+            assert_eq!(got, expected_sorted);
+        }
+    }
+
+    #[test]
+    fn retrograde_depths_match_forward_search() {
+        let mut solver = Solver::new();
+        let retro = solver.solve_retrograde_with_depth();
+        let states = enumerate_states();
+        assert_eq!(retro.len(), states.len());
+        for state in states {
+            let expected = (solver.verdict(state), solver.depth_to_win(state));
+            assert_eq!(retro[&state], expected, "{:?}", state.heights());
+        }
+    }
+
+    #[test]
+    fn fastest_win_line_alternates_to_terminal() {
+        let mut solver = Solver::new();
+        let start = BoardState::new();
+        let line = solver.fastest_win_line(start);
+        assert_eq!(line.len() as u32, solver.depth_to_win(start));
+        assert_eq!(line[0], Move::new(0, 1));
+        let end = line.iter().fold(start, |state, &mv| state.apply_move(mv));
+        assert!(end.is_terminal());
+        // Odd length: the opener makes the last move and the opponent is left with the poison.
+        assert_eq!(line.len() % 2, 1);
+    }
+
+    #[test]
+    fn optimal_opcode_line_decodes_to_mover_moves() {
+        let mut solver = Solver::new();
+        let start = BoardState::new();
+        let line = solver.fastest_win_line(start);
+        let decoded: Vec<Move> = solver
+            .optimal_opcode_line(start)
+            .into_iter()
+            .map(|op| opcode::decode(op).unwrap())
+            .collect();
+        let mover_moves: Vec<Move> = line.into_iter().step_by(2).collect();
+        assert_eq!(decoded, mover_moves);
+    }
+
+    #[test]
+    fn normal_play_can_take_the_poison() {
+        let mut solver = Solver::with_mode(GameMode::Normal);
+        let only_poison = BoardState::from_heights([4, 4, 4, 4, 4, 4, 4, 3]);
+        let eval = solver.evaluate(only_poison);
+        assert!(eval.winning);
+        assert_eq!(eval.winning_moves, vec![POISON]);
+        assert!(!Solver::new().evaluate(only_poison).winning);
+    }
+
+    #[test]
+    fn mode_agreement_covers_every_state() {
+        let (agree, disagree) = mode_agreement();
+        assert_eq!(agree + disagree, enumerate_states().len() as u64);
+        assert!(disagree > 0);
+    }
+
+    #[test]
+    fn parallel_and_serial_classification_agree() {
+        let sample: Vec<BoardState> = enumerate_states().into_iter().rev().step_by(7).collect();
+        let serial = classify_batch_serial(&sample);
+        assert_eq!(classify_batch(&sample), serial);
+        let mut solver = Solver::new();
+        for (&state, &verdict) in sample.iter().zip(&serial) {
+            assert_eq!(
+                verdict,
+                Verdict::from_winning(solver.evaluate(state).winning)
+            );
+        }
+    }
+
+    #[test]
+    fn moves_to_clear_column_counts_remaining_bites() {
+        let state = BoardState::from_heights([4, 1, 1, -1, -1, -1, -1, -1]);
+        assert_eq!(state.moves_to_clear_column(0), 0);
+        assert_eq!(state.moves_to_clear_column(1), 1);
+        assert_eq!(state.moves_to_clear_column(7), 1);
+
+        let poison_column_done = BoardState::from_heights([4, 4, 4, 4, 4, 4, 4, 3]);
+        assert_eq!(poison_column_done.moves_to_clear_column(7), 0);
+    }
+
+    #[test]
+    fn policy_fingerprint_is_stable_and_sensitive() {
+        assert_eq!(policy_fingerprint(), policy_fingerprint());
+
+        let mut table = policy_table();
+        let baseline = fingerprint_table(&table);
+        let entry = table.get_mut(&BoardState::new()).unwrap();
+        entry.winning_moves.push(Move::new(0, 0));
+        assert_ne!(fingerprint_table(&table), baseline);
+    }
+
+    #[test]
+    fn side_to_move_follows_corner_count() {
+        let start = BoardState::new();
+        assert_eq!(start.ply_count(), 0);
+        assert_eq!(start.side_to_move(), Player::One);
+
+        let opened = start.apply_move(Move::new(0, 1));
+        assert_eq!(opened.ply_count(), 1);
+        assert_eq!(opened.side_to_move(), Player::Two);
+
+        let staircase = BoardState::from_heights([2, 1, 1, 0, -1, -1, -1, -1]);
+        assert_eq!(staircase.ply_count(), 3);
+    }
+
+    #[test]
+    fn reachability_requires_staircase_with_poison() {
+        assert!(enumerate_states().iter().all(BoardState::is_reachable));
+        let jagged = BoardState::from_heights_unchecked([0, -1, 2, -1, -1, -1, -1, -1]);
+        assert!(!jagged.is_valid_staircase());
+        let poison_eaten = BoardState::from_heights([4; COLS]);
+        assert!(poison_eaten.is_valid_staircase());
+        assert!(!poison_eaten.is_reachable());
+    }
+
+    #[test]
+    fn export_progress_reaches_total() {
+        let path = std::env::temp_dir().join("chomp_export_progress_test.json");
+        let mut calls = Vec::new();
+        export_policy_json_with_progress(&path, |done, total| calls.push((done, total))).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let &(done, total) = calls.last().expect("progress callback never fired");
+        assert_eq!(done, total);
+        assert_eq!(total, enumerate_states().len());
+    }
+
+    #[test]
+    fn resumed_export_matches_uninterrupted_run() {
+        let dir = std::env::temp_dir();
+        let full = dir.join("chomp_export_full_test.json");
+        let resumed = dir.join("chomp_export_resumed_test.json");
+        export_policy_json(&full).unwrap();
+
+        // Stop after 300 states, past the first marker write at 256.
+        let mut polled = 0;
+        let done = export_policy_json_resumable(
+            &resumed,
+            false,
+            PolicyKeyFormat::Heights,
+            PolicyMoveFormat::Structured,
+            |_, _| {},
+            || {
+                polled += 1;
+                polled > 300
+            },
+        )
+        .unwrap();
+        assert!(!done);
+        assert!(export_marker_path(&resumed).exists());
+
+        let done = export_policy_json_resumable(
+            &resumed,
+            true,
+            PolicyKeyFormat::Heights,
+            PolicyMoveFormat::Structured,
+            |_, _| {},
+            || false,
+        )
+        .unwrap();
+        assert!(done);
+        assert!(!export_marker_path(&resumed).exists());
+
+        let expected = std::fs::read_to_string(&full).unwrap();
+        let actual = std::fs::read_to_string(&resumed).unwrap();
+        std::fs::remove_file(&full).ok();
+        std::fs::remove_file(&resumed).ok();
+        assert_eq!(actual, expected);
+        assert_eq!(expected.lines().count(), enumerate_states().len() + 2);
+    }
+
+    #[test]
+    fn u64_packing_round_trips() {
+        for state in enumerate_states() {
+            assert_eq!(BoardState::from_u64(state.to_u64()), Some(state));
+            let key = PolicyKeyFormat::U64.key(&state);
+            assert_eq!(PolicyKeyFormat::parse_key(&key), Some(state));
+            let key = PolicyKeyFormat::Heights.key(&state);
+            assert_eq!(PolicyKeyFormat::parse_key(&key), Some(state));
+        }
+        assert_eq!(BoardState::new().to_u64(), 0);
+        assert_eq!(BoardState::from_u64(1 << (3 * COLS)), None);
+        assert_eq!(BoardState::from_u64(6), None);
+        assert_eq!("u64".parse(), Ok(PolicyKeyFormat::U64));
+    }
+
+    #[test]
+    fn algebraic_evaluation_round_trips() {
+        let eval = Solver::new().evaluate(BoardState::new());
+        let repr = AlgebraicRepr::from(AlgebraicEvaluation(eval.clone()));
+        assert_eq!(repr.winning_moves, vec!["b1".to_string()]);
+        let back = AlgebraicEvaluation::try_from(repr.clone()).unwrap().0;
+        assert_eq!(back.winning, eval.winning);
+        assert_eq!(back.winning_moves, eval.winning_moves);
+
+        let json = serde_json::to_string(&AlgebraicEvaluation(eval.clone())).unwrap();
+        let parsed: AlgebraicEvaluation = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.0.winning_moves, eval.winning_moves);
+
+        let bad = AlgebraicRepr {
+            winning_moves: vec!["z9".to_string()],
+            ..repr
+        };
+        assert!(AlgebraicEvaluation::try_from(bad).is_err());
+    }
+
+    #[test]
+    fn u64_keyed_export_loads_back() {
+        let path = std::env::temp_dir().join("chomp_export_u64_test.json");
+        export_policy_json_resumable(
+            &path,
+            false,
+            PolicyKeyFormat::U64,
+            PolicyMoveFormat::Structured,
+            |_, _| {},
+            || false,
+        )
+        .unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        let loaded = load_policy_json(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert!(text.starts_with("{\"version\": 2, \"states\": {"));
+        assert!(text.lines().nth(1).unwrap().starts_with("  \"0\": "));
+        let mut states = enumerate_states();
+        states.sort();
+        assert_eq!(loaded.keys().copied().collect::<Vec<_>>(), states);
+        let mut solver = Solver::new();
+        for (state, eval) in loaded {
+            assert_eq!(eval.winning_moves, solver.evaluate(state).winning_moves);
+        }
+    }
+
+    #[test]
+    fn game_length_bounds_span_fastest_and_slowest_games() {
+        let (min, max) = game_length_bounds();
+        assert!(min <= max);
+        // Fastest finish: clear everything left of the poison column, then
+        // the candies above the poison.
+        let fastest = [Move::new(4, 6), Move::new(3, 7)]
+            .iter()
+            .fold(BoardState::new(), |state, &mv| state.apply_move(mv));
+        assert!(fastest.is_terminal());
+        assert_eq!(min, 2);
+        // Slowest: one candy per bite.
+        assert_eq!(max, BoardState::new().remaining_candies());
+    }
+
+    #[test]
+    fn fully_forced_sequences_exist() {
+        let mut two = fully_forced_positions(2);
+        assert!(!two.is_empty());
+        let one = fully_forced_positions(1);
+        assert!(two.iter().all(|state| one.contains(state)));
+        assert_eq!(one.len(), unique_winning_move_positions().len());
+        let mut three = fully_forced_positions(3);
+        three.sort();
+        two.sort();
+        assert_eq!(three, two);
+        // Nothing stays forced for four plies.
+        assert!(fully_forced_positions(4).is_empty());
+    }
+
+    #[test]
+    fn distinct_games_on_tiny_boards() {
+        // Only the bottom row's last three cells remain: b→c or c directly.
+        let one_by_three = BoardState::from_heights([4, 4, 4, 4, 4, 3, 3, 3]);
+        assert_eq!(distinct_games(one_by_three), 2);
+        // 2×2 corner around the poison: three openers, one of which branches twice.
+        let two_by_two = BoardState::from_heights([4, 4, 4, 4, 4, 4, 2, 2]);
+        assert_eq!(distinct_games(two_by_two), 4);
+        let only_poison = BoardState::from_heights([4, 4, 4, 4, 4, 4, 4, 3]);
+        assert_eq!(distinct_games(only_poison), 1);
+        assert!(distinct_games(BoardState::new()) > distinct_games(two_by_two));
+    }
+
+    #[test]
+    fn tiny_node_budget_limits_the_full_board() {
+        let mut solver = Solver::with_node_budget(10);
+        let bounded = solver.evaluate_bounded(BoardState::new());
+        assert!(bounded.budget_limited);
+        assert!(solver.stats().nodes_expanded <= 10);
+        assert_eq!(
+            bounded.evaluation.winning,
+            !bounded.evaluation.winning_moves.is_empty()
+        );
+        assert_eq!(solver.verify_invariants(), Ok(()));
+
+        let only_poison = BoardState::from_heights([4, 4, 4, 4, 4, 4, 4, 3]);
+        assert!(!solver.evaluate_bounded(only_poison).budget_limited);
+        let mut unbounded = Solver::new();
+        let exact = unbounded.evaluate_bounded(BoardState::new());
+        assert!(!exact.budget_limited);
+        assert_eq!(exact.evaluation.winning_moves, vec![Move::new(0, 1)]);
+    }
+
+    #[test]
+    fn budget_fallback_uses_verdict_only_results() {
+        let start = BoardState::new();
+        let mut solver = Solver::with_node_budget(1);
+        assert_eq!(
+            solver.verdict(start.apply_move(Move::new(0, 1))),
+            Verdict::Loss
+        );
+        let bounded = solver.evaluate_bounded(start);
+        assert!(bounded.budget_limited);
+        assert!(bounded.evaluation.winning);
+        assert_eq!(bounded.evaluation.winning_moves, vec![Move::new(0, 1)]);
+    }
+
+    #[test]
+    fn cancelled_solve_keeps_finished_subtrees() {
+        let start = BoardState::new();
+        let mut cold = Solver::new();
+        cold.evaluate(start);
+        let cold_nodes = cold.stats().nodes_expanded;
+
+        let mut solver = Solver::new();
+        let mut polls = 0;
+        let interrupted = solver.evaluate_with_cancel(start, || {
+            polls += 1;
+            polls > cold_nodes / 2
+        });
+        assert!(interrupted.is_none());
+        let before_resume = solver.stats().nodes_expanded;
+        assert!(solver
+            .cache
+            .values()
+            .all(|e| e.winning != e.winning_moves.is_empty()));
+
+        let eval = solver.evaluate(start);
+        let resumed_nodes = solver.stats().nodes_expanded - before_resume;
+        assert_eq!(eval.winning_moves, vec![Move::new(0, 1)]);
+        assert!(resumed_nodes < cold_nodes);
+    }
+
+    #[test]
+    fn mirror_reflects_across_the_poison_diagonal() {
+        assert_eq!(Move::new(4, 6).mirror(), Some(Move::new(3, 7)));
+        assert_eq!(Move::new(1, 5).mirror(), Some(Move::new(2, 4)));
+        assert_eq!(POISON.mirror(), Some(POISON));
+        assert_eq!(Move::new(0, 1).mirror(), None);
+    }
+
+    #[test]
+    fn corner_bite_on_a_square_board_is_mirrorable() {
+        // A 4×4 board in the poison corner.
+        let square = BoardState::from_heights([4, 4, 4, 4, 0, 0, 0, 0]);
+        let mut solver = Solver::new();
+        assert_eq!(solver.mirrorable_wins(square), vec![Move::new(3, 6)]);
+        assert!(solver.mirrorable_wins(BoardState::new()).is_empty());
+    }
+
+    #[test]
+    fn opening_has_one_critical_candy() {
+        let mut solver = Solver::new();
+        let start = BoardState::new();
+        let criticality = start.candy_criticality(&mut solver);
+        assert_eq!(criticality.len(), ROWS * COLS - 1);
+        let winning: Vec<Move> = criticality
+            .iter()
+            .filter(|&&(_, keeps_win)| keeps_win)
+            .map(|&(mv, _)| mv)
+            .collect();
+        assert_eq!(winning, vec![Move::new(0, 1)]);
+    }
+
+    #[test]
+    fn winning_finishers_leave_only_poison() {
+        let one_left = BoardState::from_heights([4, 4, 4, 4, 4, 4, 3, 3]);
+        assert_eq!(one_left.winning_finishers(), vec![Move::new(4, 6)]);
+
+        // One candy on each side of the poison: every bite leaves the other one.
+        let l_shape = BoardState::from_heights([4, 4, 4, 4, 4, 4, 3, 2]);
+        assert!(l_shape.winning_finishers().is_empty());
+        assert!(BoardState::new().winning_finishers().is_empty());
+    }
+
+    #[test]
+    fn diagram_round_trips_through_display() {
+        for state in enumerate_states() {
+            assert_eq!(BoardState::from_diagram(&state.to_string()), Some(state));
+        }
+        let opened =
+            ". . o o o o o o\no o o o o o o o\no o o o o o o o\no o o o o o o o\no o o o o o o X";
+        assert_eq!(
+            BoardState::from_diagram(opened),
+            Some(BoardState::new().apply_move(Move::new(0, 1)))
+        );
+        let floating =
+            "o o o o o o o o\n. o o o o o o o\no o o o o o o o\no o o o o o o o\no o o o o o o X";
+        assert_eq!(BoardState::from_diagram(floating), None);
+        assert_eq!(BoardState::from_diagram("o o\no o"), None);
+    }
+
+    #[test]
+    fn compact_form_round_trips() {
+        let opened = BoardState::new().apply_move(Move::new(0, 1));
+        assert_eq!(opened.to_compact(), "11000000");
+        for state in enumerate_states() {
+            assert_eq!(BoardState::from_compact(&state.to_compact()), Some(state));
+        }
+        assert_eq!(BoardState::from_compact("10100000"), None);
+        assert_eq!(BoardState::from_compact("55555555"), None);
+        assert_eq!(BoardState::from_compact("1100"), None);
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let path = std::env::temp_dir().join("chomp_save_load_test.txt");
+        let state = BoardState::from_heights([3, 2, 2, 0, 0, -1, -1, -1]);
+        state.save(&path).unwrap();
+        let loaded = BoardState::load(&path).unwrap();
+        std::fs::write(&path, "12000000\n").unwrap();
+        let rejected = BoardState::load(&path);
+        std::fs::remove_file(&path).ok();
+        assert_eq!(loaded, state);
+        assert!(rejected.is_err());
+    }
+
+    #[test]
+    fn every_move_from_p_position_is_refuted() {
+        let mut solver = Solver::new();
+        let p_position = BoardState::new().apply_move(Move::new(0, 1));
+        assert!(!solver.evaluate(p_position).winning);
+
+        let explanation = solver.explain_loss(p_position);
+        let moves: Vec<Move> = explanation.refutations.iter().map(|&(mv, _)| mv).collect();
+        assert_eq!(moves, p_position.legal_moves());
+        for (mv, reply) in explanation.refutations {
+            let after = p_position.apply_move(mv);
+            assert!(after.legal_moves().contains(&reply));
+            assert!(!solver.evaluate(after.apply_move(reply)).winning);
+        }
+    }
+
+    #[test]
+    fn eat_directions_remove_different_cells() {
+        let start = BoardState::new();
+        let mv = Move::new(2, 3);
+        let up_left = start.apply_move_toward(mv, EatDirection::UpLeft);
+        let down_right = start.apply_move_toward(mv, EatDirection::DownRight);
+        let up_left_moves = up_left.legal_moves_toward(EatDirection::UpLeft);
+        let down_right_moves = down_right.legal_moves_toward(EatDirection::DownRight);
+
+        // 3×4 cells eaten toward the top-left, 3×5 toward the bottom-right.
+        assert_eq!(up_left_moves.len(), ROWS * COLS - 12 - 1);
+        assert_eq!(down_right_moves.len(), ROWS * COLS - 15 - 1);
+        assert!(!up_left_moves.contains(&Move::new(0, 0)));
+        assert!(up_left_moves.contains(&Move::new(4, 6)));
+        assert!(!down_right_moves.contains(&Move::new(4, 7)));
+        assert!(down_right_moves.contains(&Move::new(1, 3)));
+
+        assert_eq!(EatDirection::DownRight.poison(), Move::new(0, 0));
+        assert_eq!(EatDirection::UpRight.poison(), Move::new(4, 0));
+        assert!(!down_right_moves.contains(&EatDirection::DownRight.poison()));
+        assert_eq!(
+            start.legal_moves_toward(EatDirection::UpLeft),
+            start.legal_moves()
+        );
+    }
+
+    #[test]
+    fn urgency_is_higher_near_the_end() {
+        let mut solver = Solver::new();
+        let open = solver.urgency(BoardState::new());
+        let last_candy = solver.urgency(BoardState::from_heights([4, 4, 4, 4, 4, 4, 4, 2]));
+        assert!((open - 1.0 / 39.0).abs() < 1e-9);
+        assert_eq!(last_candy, 1.0);
+        assert!(last_candy > open);
+        assert_eq!(
+            solver.urgency(BoardState::from_heights([4, 4, 4, 4, 4, 4, 4, 3])),
+            0.0
+        );
+    }
+
+    #[test]
+    fn opening_move_is_tagged_unique() {
+        let mut solver = Solver::new();
+        let analysis = solver.analyze_moves(BoardState::new());
+        let winners: Vec<&MoveAnalysis> = analysis.iter().filter(|a| a.winning).collect();
+        assert_eq!(winners.len(), 1);
+        assert_eq!(winners[0].mv, Move::new(0, 1));
+        assert_eq!(winners[0].margin, Some(WinMargin::Unique));
+        assert!(analysis
+            .iter()
+            .filter(|a| !a.winning)
+            .all(|a| a.margin.is_none()));
+
+        let state = enumerate_states()
+            .into_iter()
+            .find(|&s| solver.evaluate(s).winning_moves.len() > 1)
+            .unwrap();
+        assert!(solver
+            .analyze_moves(state)
+            .iter()
+            .filter(|a| a.winning)
+            .all(|a| a.margin == Some(WinMargin::OneOfSeveral)));
+    }
+
+    #[test]
+    fn shortest_loss_line_ends_on_the_poison() {
+        let mut solver = Solver::new();
+        let only_poison = BoardState::from_heights([4, 4, 4, 4, 4, 4, 4, 3]);
+        assert_eq!(solver.shortest_loss_line(only_poison), vec![POISON]);
+
+        // Equal one-candy arms around the poison: we take one, they take the other.
+        let l_shape = BoardState::from_heights([4, 4, 4, 4, 4, 4, 3, 2]);
+        assert_eq!(solver.shortest_loss_line(l_shape).len(), 3);
+
+        let lost = BoardState::new().apply_move(Move::new(0, 1));
+        let quick = solver.shortest_loss_line(lost);
+        let slow = solver.fastest_win_line(lost);
+        assert!(quick.len() <= slow.len() + 1);
+        assert_eq!(quick.last(), Some(&POISON));
+        assert!(solver.shortest_loss_line(BoardState::new()).is_empty());
+    }
+
+    #[test]
+    fn solution_summary_names_the_winner_and_opening() {
+        let summary = solution_summary();
+        assert!(summary.starts_with("5×8 Chomping Glass is a first player win."));
+        assert!(summary.contains("Unique winning opening: b1"));
+        assert!(summary.contains(&format!(
+            "Reachable positions: {}",
+            enumerate_states().len()
+        )));
+        assert!(summary.contains("with a unique winning opening: 39 of 40"));
+        assert!(summary.contains("Opening moves: 39\n"));
+    }
+
+    #[test]
+    fn opening_branching_factor_counts_every_cell_but_the_poison() {
+        assert_eq!(opening_branching_factor(), ROWS * COLS - 1);
+        assert_eq!(opening_branching_factor(), 39);
+        let after = BoardState::new().apply_move(Move::new(0, 1));
+        assert_eq!(branching_factor(after), 37);
+        assert_eq!(
+            branching_factor(BoardState::from_heights([4, 4, 4, 4, 4, 4, 4, 3])),
+            0
+        );
+    }
+
+    #[test]
+    fn several_poisons_restrict_legal_moves() {
+        let start = BoardState::new();
+        let single: HashSet<Move> = [POISON].into_iter().collect();
+        for state in enumerate_states() {
+            assert_eq!(state.legal_moves_with_poisons(&single), state.legal_moves());
+        }
+
+        let second = Move::new(4, 6);
+        let two: HashSet<Move> = [POISON, second].into_iter().collect();
+        let moves = start.legal_moves_with_poisons(&two);
+        assert_eq!(moves.len(), ROWS * COLS - 2);
+        assert!(!moves.contains(&POISON) && !moves.contains(&second));
+
+        // An interior poison also rules out every bite that would reach it.
+        let interior: HashSet<Move> = [POISON, Move::new(2, 3)].into_iter().collect();
+        assert_eq!(
+            start.legal_moves_with_poisons(&interior).len(),
+            ROWS * COLS - 15
+        );
+
+        let only_poisons = BoardState::from_heights([4, 4, 4, 4, 4, 4, 3, 3]);
+        assert!(!only_poisons.is_terminal());
+        assert!(only_poisons.is_terminal_with_poisons(&two));
+        let diagram = only_poisons.render_with_poisons(&two);
+        assert_eq!(diagram.lines().last().unwrap(), "  .  .  .  .  .  .  X  X");
+        assert_eq!(start.render_with_poisons(&single), start.to_string());
+    }
+
+    #[test]
+    fn poison_exposure_counts_corner_neighbors() {
+        assert_eq!(BoardState::new().poison_exposure(), 2);
+        // Everything left of the poison column is gone; the candy above remains.
+        let one_left = BoardState::from_heights([4, 4, 4, 4, 4, 4, 4, -1]);
+        assert_eq!(one_left.poison_exposure(), 1);
+        let only_poison = BoardState::from_heights([4, 4, 4, 4, 4, 4, 4, 3]);
+        assert_eq!(only_poison.poison_exposure(), 0);
+        assert_eq!(BoardState::from_heights([4; COLS]).poison_exposure(), 0);
+    }
+
+    #[test]
+    fn remaining_candies_skip_the_poison() {
+        assert_eq!(BoardState::new().remaining_candies(), ROWS * COLS - 1);
+        assert_eq!(
+            BoardState::from_heights([4, 4, 4, 4, 4, 4, 4, 3]).remaining_candies(),
+            0
+        );
+        assert_eq!(
+            BoardState::from_heights([1, 0, -1, -1, -1, -1, -1, -1]).remaining_candies(),
+            36
+        );
+    }
+
+    #[test]
+    fn pairing_partner_on_two_equal_columns() {
+        let mut solver = Solver::new();
+        // Two candies left of the poison and two above it.
+        let state = BoardState::from_heights([4, 4, 4, 4, 4, 4, 2, 1]);
+        let (a, b) = solver.pairing_partner(state).unwrap();
+        assert_eq!((a, b), (Move::new(4, 6), Move::new(3, 7)));
+        assert!(!solver.is_winning(state));
+        for shift in 0..2 {
+            let a = Move::new(a.row - shift, a.col);
+            let b = Move::new(b.row - shift, b.col);
+            for (theirs, ours) in [(a, b), (b, a)] {
+                let reply = state.apply_move(theirs).apply_move(ours);
+                assert!(!solver.is_winning(reply));
+            }
+        }
+
+        assert_eq!(
+            solver.pairing_partner(BoardState::from_heights([4, 4, 4, 4, 4, 4, 2, 2])),
+            None
+        );
+        assert_eq!(
+            solver.pairing_partner(BoardState::from_heights([4, 4, 4, 4, 4, 4, 4, 3])),
+            None
+        );
+        assert_eq!(solver.pairing_partner(BoardState::new()), None);
+    }
+
+    #[test]
+    fn invariants_hold_after_a_full_solve_and_catch_corruption() {
+        let mut solver = Solver::new();
+        solver.evaluate(BoardState::new());
+        assert_eq!(solver.verify_invariants(), Ok(()));
+
+        let mut flipped = solver.cache[&BoardState::new()].clone();
+        flipped.winning = false;
+        solver.cache.insert(BoardState::new(), flipped);
+        assert!(solver.verify_invariants().is_err());
+
+        let mut solver = Solver::new();
+        solver.evaluate(BoardState::new());
+        let after_b1 = BoardState::new().apply_move(Move::new(0, 1));
+        solver.cache.get_mut(&after_b1).unwrap().winning_moves = vec![Move::new(1, 0)];
+        solver.cache.get_mut(&after_b1).unwrap().winning = true;
+        assert!(solver.verify_invariants().is_err());
+    }
+
+    #[test]
+    fn transpositions_on_a_two_by_two_board() {
+        // Openers a, b, c from {a, b, c}; a leaves {b, c}, which reaches {c}
+        // or {b} a second way, and all four games end on the poison.
+        let two_by_two = BoardState::from_heights([4, 4, 4, 4, 4, 4, 2, 2]);
+        let stats = transposition_stats(two_by_two, 3);
+        assert_eq!(stats.distinct_states, 5);
+        assert_eq!(stats.sequences, 1 + 1 + 2 + 2 + 4);
+        assert_eq!(stats.max_sequences_per_state, 4);
+        assert_eq!(stats.average_sequences_per_state(), 2.0);
+
+        let shallow = transposition_stats(two_by_two, 1);
+        assert_eq!((shallow.distinct_states, shallow.sequences), (4, 4));
+        assert!(transposition_stats(BoardState::new(), 3).average_sequences_per_state() > 1.0);
+    }
+
+    #[test]
+    fn response_map_opens_with_b1() {
+        let path = std::env::temp_dir().join("chomp_response_map_test.bin");
+        export_response_map(&path, Tiebreak::First).unwrap();
+        let map = load_response_map(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(map.len(), enumerate_states().len() - 1);
+        let opening = map[&BoardState::new().to_u64()];
+        assert_eq!(opcode::decode(opening), Some(Move::new(0, 1)));
+        assert_eq!(DisplayMove::from(Move::new(0, 1)), DisplayMove::new(1, 2));
+    }
+
+    #[test]
+    fn phase_follows_the_candies_left() {
+        assert_eq!(BoardState::new().phase(), GamePhase::Opening);
+        assert_eq!(
+            BoardState::from_heights([4, 4, 4, -1, -1, -1, -1, -1]).phase(),
+            GamePhase::Midgame
+        );
+        assert_eq!(
+            BoardState::from_heights([4, 4, 4, 4, 4, 4, 4, 3]).phase(),
+            GamePhase::Endgame
+        );
+    }
+
+    #[test]
+    fn cheatsheet_groups_are_sorted_and_non_empty() {
+        let sheet = p_position_cheatsheet();
+        let mut last = None;
+        for line in sheet.lines() {
+            let (count, shapes) = line.split_once(": ").unwrap();
+            let count: usize = count.parse().unwrap();
+            assert!(last.is_none_or(|prev| prev < count));
+            last = Some(count);
+
+            let shapes: Vec<&str> = shapes.split(' ').collect();
+            assert!(!shapes.is_empty());
+            assert!(shapes.windows(2).all(|pair| pair[0] < pair[1]));
+            for shape in shapes {
+                let state = BoardState::from_compact(shape).unwrap();
+                assert_eq!(state.remaining_candies(), count);
+                assert!(!Solver::new().evaluate(state).winning);
+            }
+        }
+        assert!(sheet.starts_with("0: 55555554\n"));
+        let two: Vec<String> = losing_positions_with_candies(2)
+            .iter()
+            .map(BoardState::to_compact)
+            .collect();
+        assert!(!two.is_empty());
+        assert!(sheet
+            .lines()
+            .any(|line| line.starts_with("2: ")
+                && two.iter().all(|shape| line.contains(shape.as_str()))));
+    }
+
+    #[test]
+    fn unique_winning_move_positions_include_the_opening() {
+        let positions = unique_winning_move_positions();
+        assert!(positions.contains(&BoardState::new()));
+        let mut solver = Solver::new();
+        for state in positions {
+            assert_eq!(solver.evaluate(state).winning_moves.len(), 1);
+        }
+    }
+
+    #[test]
+    fn deepest_winning_position_has_the_maximum_depth() {
+        let (state, depth) = deepest_winning_position();
+        let mut solver = Solver::new();
+        assert!(solver.is_winning(state));
+        assert_eq!(solver.depth_to_win(state), depth);
+        let mut brute_force = 0;
+        for s in enumerate_states() {
+            if solver.is_winning(s) {
+                brute_force = brute_force.max(solver.depth_to_win(s));
+            }
+        }
+        assert_eq!(depth, brute_force);
+        assert_eq!(solver.fastest_win_line(state).len() as u32, depth);
+    }
+
+    #[test]
+    fn positions_group_by_winning_move_count() {
+        let groups = positions_by_winning_move_count();
+        assert!(groups[&1].contains(&BoardState::new()));
+        assert_eq!(groups[&1].len(), unique_winning_move_positions().len());
+        assert!(!groups.contains_key(&0));
+        let mut solver = Solver::new();
+        for (&count, states) in &groups {
+            for &state in states {
+                assert_eq!(solver.evaluate(state).winning_moves.len(), count);
+            }
+        }
+    }
+
+    #[test]
+    fn candy_scoring_can_disagree_with_avoiding_the_poison() {
+        // 2×3 corner: biting the top-left candy wins on poison, but
+        // it loses candies compared with grabbing both bottom-row candies.
+        let state = BoardState::corner(2, 3).unwrap();
+        let mut solver = Solver::new();
+        let poison_move = solver.recommend(state, Tiebreak::First).unwrap();
+        let candy_move = solver.best_candy_move(state).unwrap();
+        assert_eq!(poison_move, Move::new(3, 5));
+        assert_eq!(candy_move, Move::new(4, 6));
+        assert!(!solver.evaluate(state).winning_moves.contains(&candy_move));
+
+        let margin_after = |solver: &mut Solver, mv: Move| {
+            state.eaten_count(mv) as i32 - solver.candy_margin(state.apply_move(mv))
+        };
+        assert_eq!(margin_after(&mut solver, candy_move), 3);
+        assert_eq!(solver.candy_margin(state), 3);
+        assert!(margin_after(&mut solver, poison_move) < 3);
+
+        let after = BoardState::new().apply_move(Move::new(0, 1));
+        assert_eq!(after.score([2, 0]), 2);
+        assert_eq!(after.apply_move(Move::new(1, 0)).score([2, 1]), 1);
+    }
+
+    #[test]
+    fn optimal_tree_respects_depth() {
+        fn depth(node: &OptimalTreeNode) -> usize {
+            1 + node.children.iter().map(depth).max().unwrap_or(0)
+        }
+
+        let roots = optimal_tree(BoardState::new(), 3);
+        assert_eq!(roots.len(), 1);
+        let tree = &roots[0];
+        assert_eq!(tree.board, BoardState::new().heights);
+        assert_eq!(tree.mv, "b1");
+        assert_eq!(tree.verdict, Verdict::Win);
+        assert_eq!(depth(tree), 3);
+        let after = BoardState::new().apply_move(Move::new(0, 1));
+        assert_eq!(tree.children.len(), after.legal_moves().len());
+        assert!(tree
+            .children
+            .iter()
+            .all(|child| child.verdict == Verdict::Loss
+                && child.board == after.heights
+                && child.children.len() == 1));
+        assert!(optimal_tree(BoardState::new(), 0).is_empty());
+
+        // A losing start keeps every defense as its own root.
+        let losing_roots = optimal_tree(after, 1);
+        assert_eq!(losing_roots.len(), after.legal_moves().len());
+        assert!(losing_roots
+            .iter()
+            .all(|root| root.verdict == Verdict::Loss));
+
+        let path = std::env::temp_dir().join("chomp_optimal_tree_test.json");
+        export_optimal_tree_json(&path, 2).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        let roots: serde_json::Value = serde_json::from_str(&text).unwrap();
+        let root = &roots[0];
+        assert_eq!(root["move"], "b1");
+        assert_eq!(root["verdict"], "win");
+        assert_eq!(root["board"][0].as_i64(), Some(-1));
+        assert!(root["children"][0]["children"]
+            .as_array()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn migrating_a_v1_policy_fills_the_derived_fields() {
+        let mut solver = Solver::new();
+        let mut states: Vec<BoardState> = enumerate_states().into_iter().collect();
+        states.sort();
+        let entries: Vec<String> = states[..40]
+            .iter()
+            .map(|&state| {
+                let eval = solver.evaluate(state);
+                format!(
+                    "{:?}: {}",
+                    PolicyKeyFormat::Heights.key(&state),
+                    serde_json::to_string(&eval).unwrap()
+                )
+            })
+            .collect();
+        let v1 = format!("{{{}}}", entries.join(","));
+
+        let v2 = migrate_policy(&v1).unwrap();
+        let file: PolicyFile = serde_json::from_str(&v2).unwrap();
+        assert_eq!(file.version, POLICY_FORMAT_VERSION);
+        assert_eq!(file.states.len(), 40);
+        for (key, record) in &file.states {
+            let state = PolicyKeyFormat::parse_key(key).unwrap();
+            assert_eq!(record.depth_to_win, solver.depth_to_win(state));
+            let expected = if solver.is_winning(state) {
+                PositionType::N
+            } else {
+                PositionType::P
+            };
+            assert_eq!(record.position_type, expected);
+            assert_eq!(
+                record.evaluation.winning_moves,
+                solver.evaluate(state).winning_moves
+            );
+        }
+        assert_eq!(migrate_policy(&v2).unwrap(), v2);
+
+        let path = std::env::temp_dir().join("chomp_migrated_policy_test.json");
+        std::fs::write(&path, &v2).unwrap();
+        let loaded = load_policy_json(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(loaded.len(), 40);
+        assert!(migrate_policy(r#"{"version": 9, "states": {}}"#).is_err());
+
+        let stale =
+            r#"{"[-1, -1, -1, -1, -1, -1, -1, -1]": {"winning": false, "winning_moves": []}}"#;
+        assert!(migrate_policy(stale).is_err());
+    }
+
+    #[test]
+    fn display_move_conversions() {
+        let shown = DisplayMove::from(Move::new(0, 1));
+        assert_eq!(shown, DisplayMove::new(1, 2));
+        assert_eq!(shown.to_string(), "(1,2)");
+        assert_eq!(Move::try_from(shown), Ok(Move::new(0, 1)));
+        assert_eq!(Move::try_from(DisplayMove::new(5, 8)), Ok(POISON));
+
+        assert_eq!(
+            Move::try_from(DisplayMove::new(0, 1)),
+            Err(MoveRangeError::Row { max: ROWS })
+        );
+        assert_eq!(
+            Move::try_from(DisplayMove::new(6, 1)),
+            Err(MoveRangeError::Row { max: ROWS })
+        );
+        assert_eq!(
+            Move::try_from(DisplayMove::new(1, 9)),
+            Err(MoveRangeError::Col { max: COLS })
+        );
+        assert_eq!(
+            MoveRangeError::Col { max: COLS }.to_string(),
+            "column must be between 1 and 8"
+        );
+    }
+
+    #[test]
+    fn win_certificate_answers_every_reply() {
+        fn check(solver: &mut Solver, cert: &WinCertificate, seen: &mut HashSet<BoardState>) {
+            if !seen.insert(cert.state) {
+                return;
+            }
+            let eval = solver.evaluate(cert.state);
+            assert!(eval.winning_moves.contains(&cert.mv));
+            let next = cert.state.apply_move(cert.mv);
+            let replies: Vec<Move> = cert.replies.iter().map(|(reply, _)| *reply).collect();
+            assert_eq!(replies, next.legal_moves());
+            for (reply, answer) in &cert.replies {
+                assert_eq!(answer.state, next.apply_move(*reply));
+                check(solver, answer, seen);
+            }
+        }
+
+        let mut solver = Solver::new();
+        let start = BoardState::new();
+        let cert = solver.win_certificate(start).unwrap();
+        assert_eq!(cert.mv, Move::new(0, 1));
+        check(&mut solver, &cert, &mut HashSet::new());
+
+        let lost = start.apply_move(cert.mv);
+        assert!(solver.win_certificate(lost).is_none());
+    }
+
+    #[test]
+    fn forced_cells_follow_the_only_line() {
+        let mut solver = Solver::new();
+        // Two candies above the poison: the only win eats both at once.
+        let column = BoardState::from_heights([4, 4, 4, 4, 4, 4, 4, 1]);
+        assert_eq!(
+            solver.forced_cells(column),
+            vec![Move::new(2, 7), Move::new(3, 7)]
+        );
+
+        let forced = solver.forced_cells(BoardState::new());
+        assert!(forced.contains(&Move::new(0, 0)));
+        assert!(forced.contains(&Move::new(0, 1)));
+        assert!(!forced.contains(&POISON));
+
+        let lost = BoardState::new().apply_move(Move::new(0, 1));
+        assert!(solver.forced_cells(lost).is_empty());
+    }
+
+    #[test]
+    fn verdict_only_solve_allocates_less() {
+        let start = BoardState::new();
+        let mut full = Solver::new();
+        let mut quick = Solver::new();
+        let evaluating = count_allocations(|| {
+            for state in enumerate_states() {
+                full.evaluate(state);
+            }
+        });
+        let judging = count_allocations(|| {
+            for state in enumerate_states() {
+                quick.verdict(state);
+            }
+        });
+        assert!(judging < evaluating, "{} vs {}", judging, evaluating);
+        assert_eq!(quick.verdict(start), Verdict::Win);
+        assert_eq!(
+            quick.verdict(start.apply_move(Move::new(0, 1))),
+            Verdict::Loss
+        );
+    }
+
+    #[test]
+    fn is_winning_short_circuits() {
+        let start = BoardState::new();
+        let mut quick = Solver::new();
+        let mut full = Solver::new();
+        assert_eq!(quick.is_winning(start), full.evaluate(start).winning);
+        assert!(quick.stats().nodes_expanded <= full.stats().nodes_expanded);
+
+        for state in enumerate_states() {
+            assert_eq!(quick.is_winning(state), full.evaluate(state).winning);
+        }
+    }
+
+    #[test]
+    fn continuation_tightness_after_the_opening() {
+        let mut solver = Solver::new();
+        let start = BoardState::new();
+        let counts = solver.continuation_tightness(start, Move::new(0, 1));
+        let after = start.apply_move(Move::new(0, 1));
+        assert_eq!(counts.len(), after.legal_moves().len());
+        assert!(counts.iter().all(|&(_, n)| n >= 1));
+        // The quick-reference replies: (2,1) has one answer, (3,1) has two.
+        assert!(counts.contains(&(Move::new(1, 0), 1)));
+        assert!(counts.contains(&(Move::new(2, 0), 2)));
+        let tight = counts.iter().filter(|&&(_, n)| n == 1).count();
+        assert!(tight > 0 && tight < counts.len());
+
+        let losing = solver.continuation_tightness(start, Move::new(0, 0));
+        assert!(losing.iter().any(|&(_, n)| n == 0));
+    }
+
+    #[test]
+    fn forcing_depth_of_a_short_sequence() {
+        let mut solver = Solver::new();
+        // Four candies along the bottom row and one above the poison. Biting
+        // the leftmost leaves arms of 3 and 1: the opponent's only win evens
+        // them at 1 and 1, and after our defense their finishing bite is forced.
+        let state = BoardState::from_heights([4, 4, 4, 3, 3, 3, 3, 2]);
+        assert_eq!(solver.forcing_depth(state, Move::new(4, 3)), 2);
+        // Taking the candy above the poison leaves a single row: one forced
+        // reply eats it all.
+        assert_eq!(solver.forcing_depth(state, Move::new(3, 7)), 1);
+        // After the opener the opponent is lost with many replies to choose from.
+        assert_eq!(solver.forcing_depth(BoardState::new(), Move::new(0, 1)), 0);
+    }
+
+    #[test]
+    fn losing_moves_are_dominated_from_a_win() {
+        let mut solver = Solver::new();
+        let start = BoardState::new();
+        let dominated = solver.dominated_moves(start);
+        assert!(dominated.contains(&Move::new(0, 0)));
+        assert!(!dominated.contains(&Move::new(0, 1)));
+        assert_eq!(dominated.len(), start.legal_moves().len() - 1);
+
+        // From a lost position every move loses, yet short defenses are
+        // still dominated by the longest one.
+        let lost = start.apply_move(Move::new(0, 1));
+        let dominated = solver.dominated_moves(lost);
+        let defense = solver.best_defense(lost).unwrap();
+        assert!(!dominated.is_empty());
+        assert!(!dominated.contains(&defense));
+        assert!(dominated.len() < lost.legal_moves().len());
+    }
+
+    #[test]
+    fn slower_wins_are_dominated() {
+        let mut solver = Solver::new();
+        let state = enumerate_states()
+            .into_iter()
+            .find(|&state| {
+                let winners = solver.evaluate(state).winning_moves;
+                let depths: HashSet<u32> = winners
+                    .iter()
+                    .map(|&mv| solver.depth_to_win(state.apply_move(mv)))
+                    .collect();
+                depths.len() > 1
+            })
+            .unwrap();
+        let winners = solver.evaluate(state).winning_moves;
+        let dominated = solver.dominated_moves(state);
+        let not_winning: Vec<Move> = state
+            .legal_moves()
+            .into_iter()
+            .filter(|mv| !winners.contains(mv))
+            .collect();
+        assert!(dominated.len() > not_winning.len());
+        assert!(not_winning.iter().all(|mv| dominated.contains(mv)));
+        let fastest = solver.recommend(state, Tiebreak::Fastest).unwrap();
+        assert!(!dominated.contains(&fastest));
+        assert!(winners.iter().any(|mv| dominated.contains(mv)));
+    }
+
+    #[test]
+    fn analyze_game_finds_thrown_away_wins() {
+        let mut solver = Solver::new();
+        let perfect = [Move::new(0, 1), Move::new(1, 0)];
+        assert!(solver.analyze_game(&perfect).is_empty());
+
+        // a1 gives up the opening win; the reply then has one to lose.
+        let reply = solver.evaluate(BoardState::new().apply_move(Move::new(0, 0)));
+        let losing_reply = BoardState::new()
+            .apply_move(Move::new(0, 0))
+            .legal_moves()
+            .into_iter()
+            .find(|mv| !reply.winning_moves.contains(mv))
+            .unwrap();
+        let blunders = solver.analyze_game(&[Move::new(0, 0), losing_reply]);
+        assert_eq!(
+            blunders,
+            vec![
+                Blunder {
+                    ply: 0,
+                    mover: Player::One,
+                    played: Move::new(0, 0),
+                    winning_move: Move::new(0, 1),
+                },
+                Blunder {
+                    ply: 1,
+                    mover: Player::Two,
+                    played: losing_reply,
+                    winning_move: reply.winning_moves[0],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn three_by_four_subboard_policy() {
+        let policy = solve_subboard(3, 4);
+        assert_eq!((policy.rows, policy.cols), (3, 4));
+        // Staircases in a 3×4 box, less the one with the poison eaten.
+        assert_eq!(policy.table.len(), 34);
+        assert!(policy.opening().winning);
+        assert_eq!(policy.opening().winning_moves.len(), 1);
+        let full = solve_subboard(ROWS, COLS);
+        assert_eq!(full.table.len(), enumerate_states().len());
+        assert_eq!(full.opening().winning_moves, vec![Move::new(0, 1)]);
+    }
+
+    #[test]
+    fn winning_openings_are_just_b1() {
+        assert_eq!(winning_openings_algebraic(), ["b1"]);
+    }
+
+    #[test]
+    fn unique_winning_opening_by_board_size() {
+        assert!(has_unique_winning_opening(ROWS, COLS));
+        assert!(has_unique_winning_opening(2, 2));
+        assert!(has_unique_winning_opening(1, COLS));
+        // Only the poison: no opening wins at all.
+        assert!(!has_unique_winning_opening(1, 1));
+    }
+
+    #[test]
+    fn for_each_policy_entry_visits_every_state_once() {
+        let mut calls = 0;
+        let mut last = None;
+        for_each_policy_entry(|state, eval| {
+            calls += 1;
+            assert!(last < Some(state));
+            assert_eq!(eval.winning, !eval.winning_moves.is_empty());
+            last = Some(state);
+        });
+        assert_eq!(calls, enumerate_states().len());
+    }
+
+    #[test]
+    fn weighted_opponent_model_reduces_to_a_fixed_opponent() {
+        // The opponent always takes their first legal move.
+        fn against_first(state: BoardState, our_turn: bool) -> f64 {
+            let moves = state.legal_moves();
+            match (moves.is_empty(), our_turn) {
+                (true, ours) => f64::from(u8::from(!ours)),
+                (false, true) => moves
+                    .iter()
+                    .map(|&mv| against_first(state.apply_move(mv), false))
+                    .fold(0.0, f64::max),
+                (false, false) => against_first(state.apply_move(moves[0]), true),
+            }
+        }
+        let first_only =
+            |state: &BoardState, mv: Move| f64::from(u8::from(state.legal_moves()[0] == mv));
+        let lost = *losing_positions_with_candies(6).iter().min().unwrap();
+        assert_eq!(
+            random_opponent_winprob_weighted(lost, first_only),
+            against_first(lost, true)
+        );
+
+        let uniform = random_opponent_winprob(lost);
+        assert!(uniform > 0.0 && uniform < 1.0);
+        let greedy =
+            random_opponent_winprob_weighted(lost, |state, mv| state.eaten_count(mv) as f64);
+        assert!(greedy > 0.0 && greedy < 1.0);
+        assert!((random_opponent_winprob(BoardState::new()) - 1.0).abs() < 1e-9);
+        let only_poison = BoardState::from_heights([4, 4, 4, 4, 4, 4, 4, 3]);
+        assert_eq!(random_opponent_winprob(only_poison), 0.0);
+    }
+
+    #[test]
+    fn symmetry_class_on_a_four_by_four_corner() {
+        let square = BoardState::from_heights([4, 4, 4, 4, 0, 0, 0, 0]);
+        assert_eq!(square.symmetry_class(), SymmetryClass::Symmetric);
+        let lopsided = BoardState::from_heights([4, 4, 4, 4, 2, 0, 0, 0]);
+        let partner = BoardState::from_heights([4, 4, 4, 4, 1, 1, 0, 0]);
+        assert_eq!(lopsided.symmetry_class(), SymmetryClass::Partner(partner));
+        assert_eq!(partner.symmetry_class(), SymmetryClass::Partner(lopsided));
+        let mut solver = Solver::new();
+        assert_eq!(solver.is_winning(lopsided), solver.is_winning(partner));
+
+        assert_eq!(
+            BoardState::new().symmetry_class(),
+            SymmetryClass::Asymmetric
+        );
+        let three_wide = BoardState::from_heights([4, 4, 4, 4, 4, 0, 0, 0]);
+        assert_eq!(three_wide.symmetry_class(), SymmetryClass::Asymmetric);
+    }
+
+    #[test]
+    fn distance_to_p_by_position() {
+        let mut solver = Solver::new();
+        assert_eq!(solver.distance_to_p(BoardState::new()), 0);
+        let lost = BoardState::new().apply_move(Move::new(0, 1));
+        assert_eq!(solver.distance_to_p(lost), 1);
+        // One candy beside the poison and one above it: every line is forced.
+        let corner = BoardState::from_heights([4, 4, 4, 4, 4, 4, 3, 2]);
+        assert_eq!(solver.distance_to_p(corner), usize::MAX);
+        let only_poison = BoardState::from_heights([4, 4, 4, 4, 4, 4, 4, 3]);
+        assert_eq!(solver.distance_to_p(only_poison), usize::MAX);
+    }
+
+    #[test]
+    fn tiebreaks_pick_among_winning_moves() {
+        let mut solver = Solver::new();
+        // AI answered the opener with (3,1): two winning replies exist.
+        let state = BoardState::new()
+            .apply_move(Move::new(0, 1))
+            .apply_move(Move::new(2, 0));
+        let winning = solver.evaluate(state).winning_moves;
+        assert_eq!(
+            solver.recommend(state, Tiebreak::First),
+            winning.first().copied()
+        );
+        for tiebreak in [Tiebreak::Fastest, Tiebreak::MostEaten] {
+            let mv = solver.recommend(state, tiebreak).unwrap();
+            assert!(winning.contains(&mv));
+        }
+        assert_eq!(solver.best_defense(state), None);
+
+        let lost = BoardState::new().apply_move(Move::new(0, 1));
+        assert_eq!(solver.recommend(lost, Tiebreak::Fastest), None);
+        assert!(solver.best_defense(lost).is_some());
+        for tiebreak in [Tiebreak::First, Tiebreak::Fastest, Tiebreak::MostEaten] {
+            let ranked = solver.ranked_moves(state, tiebreak);
+            assert_eq!(ranked.len(), state.legal_moves().len());
+            assert_eq!(ranked.first().copied(), solver.recommend(state, tiebreak));
+            assert!(ranked[..winning.len()]
+                .iter()
+                .all(|mv| winning.contains(mv)));
+            assert_eq!(
+                solver.ranked_moves(lost, tiebreak).first().copied(),
+                solver.best_defense(lost)
+            );
+        }
+        assert_eq!("most-eaten".parse(), Ok(Tiebreak::MostEaten));
+        assert!("best".parse::<Tiebreak>().is_err());
+    }
+
+    #[test]
+    fn limited_enumeration_reports_overflow() {
+        assert_eq!(
+            enumerate_states_limited(10),
+            Err(EnumerationError::LimitExceeded { limit: 10 })
+        );
+        let all = enumerate_states();
+        assert_eq!(
+            enumerate_states_limited(all.len()).unwrap().len(),
+            all.len()
+        );
+    }
+
+    #[test]
+    fn reachable_states_match_the_staircase_count() {
+        assert_eq!(staircase_bound(), 1286);
+        assert!(reachable_state_count_matches_bound());
+    }
+
+    #[test]
+    fn partition_round_trips_over_reachable_states() {
+        for state in enumerate_states() {
+            let parts = state.to_partition();
+            assert_eq!(BoardState::from_partition(&parts), Some(state));
+        }
+    }
+
+    #[test]
+    fn partition_matches_hand_computed_shape() {
+        let state = BoardState::from_heights([2, 1, 1, 0, -1, -1, -1, -1]);
+        assert_eq!(state.to_partition(), vec![3, 2, 2, 1]);
+        assert!(BoardState::new().to_partition().is_empty());
+        assert_eq!(BoardState::from_partition(&[1, 2]), None);
+        assert_eq!(BoardState::from_partition(&[5; COLS]), None);
+    }
+}
@@ -0,0 +1,192 @@
+//! Pluggable move-selection strategies and tools for auditing them.
+
+use crate::record::GameRecord;
+use crate::{BoardState, Move, Player, Solver};
+
+/// Something that picks a move for the side to play.
+pub trait Strategy {
+    /// Choose a move for `player` from `state`, or `None` if only the poison is left.
+    fn choose_move(&mut self, state: &BoardState, player: Player) -> Option<Move>;
+}
+
+/// Perfect play: the first winning move, or the first legal move when lost.
+#[derive(Default)]
+pub struct OptimalStrategy {
+    solver: Solver,
+}
+
+impl OptimalStrategy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Strategy for OptimalStrategy {
+    fn choose_move(&mut self, state: &BoardState, _player: Player) -> Option<Move> {
+        let eval = self.solver.evaluate(*state);
+        eval.winning_moves
+            .first()
+            .copied()
+            .or_else(|| state.legal_moves().first().copied())
+    }
+}
+
+/// Uniformly random legal moves from a small seeded generator (SplitMix64),
+/// so the same seed always replays the same game.
+#[derive(Clone, Debug)]
+pub struct RandomStrategy {
+    state: u64,
+}
+
+impl RandomStrategy {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+impl Strategy for RandomStrategy {
+    fn choose_move(&mut self, state: &BoardState, _player: Player) -> Option<Move> {
+        let moves = state.legal_moves();
+        if moves.is_empty() {
+            return None;
+        }
+        let index = (self.next_u64() % moves.len() as u64) as usize;
+        Some(moves[index])
+    }
+}
+
+/// Self-play `strategy` from the empty board until only the poison is left.
+/// The side that made the last move wins, since the other must take the poison.
+pub fn self_play<S: Strategy>(strategy: &mut S) -> GameRecord {
+    let mut state = BoardState::new();
+    let mut player = Player::One;
+    let mut moves = Vec::new();
+    while let Some(mv) = strategy.choose_move(&state, player) {
+        moves.push(mv);
+        state = state.apply_move(mv);
+        player = player.opponent();
+    }
+    GameRecord {
+        result: (!moves.is_empty()).then_some(player.opponent()),
+        ..GameRecord::new(moves)
+    }
+}
+
+/// Self-play `strategy` from the empty board and report the first ply at which
+/// the mover held a win but played a losing move, as `(ply, played, optimal)`.
+///
+/// `first_player` is the side handed to the strategy on ply 0. Moves from an
+/// already-lost position are never counted as mistakes.
+pub fn first_mistake<S: Strategy>(
+    strategy: &mut S,
+    first_player: Player,
+) -> Option<(usize, Move, Move)> {
+    let mut solver = Solver::new();
+    let mut state = BoardState::new();
+    let mut player = first_player;
+    let mut ply = 0;
+
+    while let Some(played) = strategy.choose_move(&state, player) {
+        if let Some(blunder) = solver.blunder(state, ply, player, played) {
+            return Some((blunder.ply, blunder.played, blunder.winning_move));
+        }
+        state = state.apply_move(played);
+        player = player.opponent();
+        ply += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Plays perfectly except on one ply, where it takes the first losing move.
+    struct BlunderAt {
+        ply: usize,
+        calls: usize,
+        inner: OptimalStrategy,
+    }
+
+    impl Strategy for BlunderAt {
+        fn choose_move(&mut self, state: &BoardState, player: Player) -> Option<Move> {
+            let ply = self.calls;
+            self.calls += 1;
+            if ply == self.ply {
+                let mut solver = Solver::new();
+                return solver
+                    .analyze_moves(*state)
+                    .into_iter()
+                    .find(|a| !a.winning)
+                    .map(|a| a.mv);
+            }
+            self.inner.choose_move(state, player)
+        }
+    }
+
+    #[test]
+    fn seeded_random_games_repeat_and_finish() {
+        let first = self_play(&mut RandomStrategy::new(7));
+        assert_eq!(self_play(&mut RandomStrategy::new(7)), first);
+        let boards = first.replay();
+        assert_eq!(boards.len(), first.moves.len() + 1);
+        assert!(boards.last().unwrap().is_terminal());
+        let last_mover = match first.moves.len() % 2 {
+            1 => Player::One,
+            _ => Player::Two,
+        };
+        assert_eq!(first.result, Some(last_mover));
+
+        let optimal = self_play(&mut OptimalStrategy::new());
+        assert_eq!(optimal.result, Some(Player::One));
+    }
+
+    #[test]
+    fn optimal_self_play_has_no_mistake() {
+        assert_eq!(
+            first_mistake(&mut OptimalStrategy::new(), Player::One),
+            None
+        );
+    }
+
+    #[test]
+    fn flawed_strategy_mistake_is_located() {
+        let mut strategy = BlunderAt {
+            ply: 2,
+            calls: 0,
+            inner: OptimalStrategy::new(),
+        };
+        let (ply, played, optimal) = first_mistake(&mut strategy, Player::One).unwrap();
+        assert_eq!(ply, 2);
+        assert_ne!(played, optimal);
+
+        let mut replay = OptimalStrategy::new();
+        let mut before = BoardState::new();
+        for player in [Player::One, Player::Two] {
+            before = before.apply_move(replay.choose_move(&before, player).unwrap());
+        }
+        let eval = Solver::new().evaluate(before);
+        assert!(eval.winning_moves.contains(&optimal));
+        assert!(!eval.winning_moves.contains(&played));
+
+        let mut strategy = BlunderAt {
+            ply: 2,
+            calls: 0,
+            inner: OptimalStrategy::new(),
+        };
+        let game = self_play(&mut strategy);
+        let first = Solver::new().analyze_game(&game.moves)[0];
+        assert_eq!(
+            (first.ply, first.played, first.winning_move),
+            (ply, played, optimal)
+        );
+    }
+}